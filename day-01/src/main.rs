@@ -1,8 +1,42 @@
+mod sonar;
+
+use sonar::SonarSweep;
+
 type Result<T, E = Box<dyn std::error::Error + Send + Sync>> = std::result::Result<T, E>;
 
 fn main() -> Result<()> {
-    let input = include_str!("input.txt");
-    let parsed = parse(input)?;
+    let mut args = std::env::args().skip(1);
+    let mut window = None;
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--window" => window = Some(args.next().ok_or("--window needs a size")?.parse()?),
+            _ => path = Some(arg),
+        }
+    }
+
+    if let Some(window) = window {
+        let mut sweep = SonarSweep::new(window);
+        match path {
+            Some(path) => sweep.read(std::io::BufReader::new(std::fs::File::open(path)?))?,
+            None => sweep.read(include_str!("input.txt").as_bytes())?,
+        }
+        let report = sweep.report();
+        println!("readings: {}", report.readings);
+        println!("window increases: {}", report.window_increases);
+        println!("longest increasing run: {}", report.longest_increasing_run);
+        match report.max_drop {
+            Some(drop) => println!("max drop: {}", drop),
+            None => println!("max drop: none"),
+        }
+        return Ok(());
+    }
+
+    let input = match path {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => include_str!("input.txt").to_string(),
+    };
+    let parsed = parse(&input)?;
     let increases = count_increases(&parsed);
     println!("{}", increases);
     let increases = count_increase_windows(&parsed);
@@ -19,34 +53,15 @@ fn parse(input: &str) -> Result<Vec<u64>> {
 }
 
 fn count_increases(inputs: &[u64]) -> usize {
-    let windows = inputs.windows(2);
-    let mut count = 0;
-    for window in windows {
-        let first = window[0];
-        let second = window[1];
-        if second > first {
-            count += 1;
-        }
-    }
-    count
+    let mut sweep = SonarSweep::new(1);
+    sweep.extend(inputs.iter().copied());
+    sweep.report().window_increases as usize
 }
 
 fn count_increase_windows(inputs: &[u64]) -> usize {
-    let windows = inputs.windows(3);
-    let mut count = 0;
-    let mut prev: Option<u64> = None;
-
-    for window in windows {
-        let sum = window.iter().copied().sum();
-        if let Some(prev_val) = prev {
-            if sum > prev_val {
-                count += 1;
-            }
-        }
-        prev = Some(sum);
-    }
-
-    count
+    let mut sweep = SonarSweep::new(3);
+    sweep.extend(inputs.iter().copied());
+    sweep.report().window_increases as usize
 }
 
 #[cfg(test)]
@@ -0,0 +1,162 @@
+//! Analyzes depth readings one at a time, so logs of any length can be read in constant memory.
+
+use std::io::BufRead;
+
+/// Counts how often a sliding window of `window` readings grows, along with the longest run of
+/// increasing readings and the biggest drop between two consecutive readings.
+///
+/// Consecutive windows share all but one reading, so the later window has the larger sum exactly
+/// when the reading it gains is larger than the one it loses. Only the last `window` readings need
+/// to be kept, in a ring buffer.
+#[derive(Debug, Clone)]
+pub struct SonarSweep {
+    ring: Vec<u64>,
+    window: usize,
+    next: usize,
+    readings: u64,
+    window_increases: u64,
+    run: u64,
+    longest_run: u64,
+    max_drop: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Report {
+    pub readings: u64,
+    /// How many windows had a larger sum than the one before.
+    pub window_increases: u64,
+    /// The most readings in a row that each went deeper than the last.
+    pub longest_increasing_run: u64,
+    /// The largest amount a reading was shallower than the one before, if any was.
+    pub max_drop: Option<u64>,
+}
+
+impl SonarSweep {
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "Window must hold at least one reading");
+        Self {
+            ring: Vec::with_capacity(window),
+            window,
+            next: 0,
+            readings: 0,
+            window_increases: 0,
+            run: 0,
+            longest_run: 0,
+            max_drop: None,
+        }
+    }
+
+    pub fn push(&mut self, depth: u64) {
+        let previous = self.last();
+        match previous {
+            Some(previous) if depth > previous => self.run += 1,
+            _ => self.run = 1,
+        }
+        self.longest_run = std::cmp::max(self.longest_run, self.run);
+        if let Some(previous) = previous {
+            if depth < previous {
+                let drop = previous - depth;
+                self.max_drop = Some(self.max_drop.map_or(drop, |max| std::cmp::max(max, drop)));
+            }
+        }
+
+        if self.ring.len() < self.window {
+            self.ring.push(depth);
+        } else {
+            // `next` is the oldest reading, which is leaving the window.
+            if depth > self.ring[self.next] {
+                self.window_increases += 1;
+            }
+            self.ring[self.next] = depth;
+        }
+        self.next = (self.next + 1) % self.window;
+        self.readings += 1;
+    }
+
+    /// Reads one depth per line.
+    pub fn read<R: BufRead>(&mut self, reader: R) -> crate::Result<()> {
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let depth = line
+                .trim()
+                .parse::<u64>()
+                .map_err(|err| format!("Line {}: {}", idx + 1, err))?;
+            self.push(depth);
+        }
+        Ok(())
+    }
+
+    pub fn report(&self) -> Report {
+        Report {
+            readings: self.readings,
+            window_increases: self.window_increases,
+            longest_increasing_run: self.longest_run,
+            max_drop: self.max_drop,
+        }
+    }
+
+    fn last(&self) -> Option<u64> {
+        if self.ring.is_empty() {
+            None
+        } else {
+            Some(self.ring[(self.next + self.window - 1) % self.window])
+        }
+    }
+}
+
+impl Extend<u64> for SonarSweep {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, iter: I) {
+        for depth in iter {
+            self.push(depth);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &[u64] = &[199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn example() {
+        let mut sweep = SonarSweep::new(3);
+        sweep.read(include_str!("example.txt").as_bytes()).unwrap();
+        assert_eq!(
+            sweep.report(),
+            Report {
+                readings: 10,
+                window_increases: 5,
+                longest_increasing_run: 4,
+                max_drop: Some(10),
+            }
+        );
+    }
+
+    #[test]
+    fn windows() {
+        for window in 1..=12 {
+            let mut sweep = SonarSweep::new(window);
+            sweep.extend(EXAMPLE.iter().copied());
+
+            let sums: Vec<u64> = EXAMPLE.windows(window).map(|w| w.iter().sum()).collect();
+            let expected = sums.windows(2).filter(|w| w[1] > w[0]).count() as u64;
+            assert_eq!(sweep.report().window_increases, expected, "{}", window);
+        }
+    }
+
+    #[test]
+    fn edges() {
+        let sweep = SonarSweep::new(1);
+        assert_eq!(sweep.report().longest_increasing_run, 0);
+        assert_eq!(sweep.report().max_drop, None);
+
+        let mut sweep = SonarSweep::new(2);
+        sweep.extend([5, 5, 5]);
+        assert_eq!(sweep.report().longest_increasing_run, 1);
+        assert_eq!(sweep.report().max_drop, None);
+
+        let mut sweep = SonarSweep::new(1);
+        assert!(sweep.read("1\n2\nthree\n".as_bytes()).is_err());
+    }
+}
@@ -1,38 +1,89 @@
+mod script;
+mod trajectory;
+
+use std::fmt::Display;
 use std::str::FromStr;
 
 type Result<T, E = Box<dyn std::error::Error + Send + Sync>> = std::result::Result<T, E>;
 
 fn main() -> Result<()> {
-    let input = include_str!("input.txt");
-    let parsed = parse(input)?;
-    let mut position = Position::default();
-    for command in &parsed {
-        position.apply(command);
+    let mut args = std::env::args().skip(1);
+    let mut path = None;
+    let mut csv = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--csv" => csv = Some(args.next().ok_or("--csv needs a path")?),
+            _ => path = Some(arg),
+        }
     }
-    println!(
-        "{} ({:?})",
-        position.horizontal_position * position.depth,
-        position
-    );
 
-    let mut position = Position2::default();
-    for command in &parsed {
-        position.apply(command);
+    let input = match path {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => include_str!("input.txt").to_string(),
+    };
+    let program = parse(&input)?;
+
+    let mut file = match csv {
+        Some(path) => Some(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => None,
+    };
+    let mut trajectory = match &mut file {
+        Some(file) => Some(trajectory::Trajectory::new(
+            file,
+            &["part_one", "part_two"],
+        )?),
+        None => None,
+    };
+    let mut position = Position::default();
+    let mut position2 = Position2::default();
+    dive(
+        &program,
+        &mut [&mut position, &mut position2],
+        trajectory.as_mut(),
+    )?;
+    println!("{} ({:?})", position.product(), position);
+    println!("{} ({:?})", position2.product(), position2);
+
+    if let Some(mut file) = file {
+        std::io::Write::flush(&mut file)?;
     }
-    println!(
-        "{} ({:?})",
-        position.horizontal_position * position.depth,
-        position
-    );
 
     Ok(())
 }
 
-fn parse(input: &str) -> Result<Vec<Command>> {
-    input
-        .lines()
-        .map(|line| line.parse::<Command>().map_err(|err| err.into()))
-        .collect::<Result<_>>()
+/// Runs the script on every submarine at once, recording where they go if asked to.
+fn dive(
+    program: &[script::Statement],
+    submarines: &mut [&mut dyn Submarine],
+    mut trajectory: Option<&mut trajectory::Trajectory>,
+) -> std::io::Result<()> {
+    script::run(program, &mut |command| {
+        for submarine in submarines.iter_mut() {
+            submarine.apply(&command);
+        }
+        match trajectory.as_deref_mut() {
+            Some(trajectory) => trajectory.record(&command, submarines),
+            None => Ok(()),
+        }
+    })
+}
+
+/// Parses a script (see `script`).
+fn parse(input: &str) -> Result<Vec<script::Statement>> {
+    Ok(script::parse(input)?)
+}
+
+/// Something that can follow commands.
+trait Submarine {
+    fn apply(&mut self, command: &Command);
+
+    fn horizontal_position(&self) -> i64;
+
+    fn depth(&self) -> i64;
+
+    fn product(&self) -> i64 {
+        self.horizontal_position() * self.depth()
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
@@ -59,13 +110,23 @@ impl FromStr for Command {
     }
 }
 
+impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Command::Forward(distance) => write!(f, "forward {}", distance),
+            Command::Down(distance) => write!(f, "down {}", distance),
+            Command::Up(distance) => write!(f, "up {}", distance),
+        }
+    }
+}
+
 #[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
 struct Position {
     depth: i64,
     horizontal_position: i64,
 }
 
-impl Position {
+impl Submarine for Position {
     fn apply(&mut self, command: &Command) {
         match command {
             Command::Down(distance) => {
@@ -79,6 +140,14 @@ impl Position {
             }
         }
     }
+
+    fn horizontal_position(&self) -> i64 {
+        self.horizontal_position
+    }
+
+    fn depth(&self) -> i64 {
+        self.depth
+    }
 }
 
 #[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
@@ -88,7 +157,7 @@ struct Position2 {
     aim: i64,
 }
 
-impl Position2 {
+impl Submarine for Position2 {
     fn apply(&mut self, command: &Command) {
         match command {
             Command::Down(distance) => {
@@ -103,6 +172,14 @@ impl Position2 {
             }
         }
     }
+
+    fn horizontal_position(&self) -> i64 {
+        self.horizontal_position
+    }
+
+    fn depth(&self) -> i64 {
+        self.depth
+    }
 }
 
 #[cfg(test)]
@@ -202,11 +279,9 @@ mod test {
     #[test]
     fn test_1() -> Result<()> {
         let str = include_str!("example.txt");
-        let parsed = parse(str)?;
+        let program = parse(str)?;
         let mut position = Position::default();
-        for command in &parsed {
-            position.apply(command);
-        }
+        dive(&program, &mut [&mut position], None)?;
         assert_eq!(
             position,
             Position {
@@ -221,11 +296,9 @@ mod test {
     #[test]
     fn test_2() -> Result<()> {
         let str = include_str!("example.txt");
-        let parsed = parse(str)?;
+        let program = parse(str)?;
         let mut position = Position2::default();
-        for command in &parsed {
-            position.apply(command);
-        }
+        dive(&program, &mut [&mut position], None)?;
         assert_eq!(position.horizontal_position, 15);
         assert_eq!(position.depth, 60);
 
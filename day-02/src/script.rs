@@ -0,0 +1,291 @@
+//! The command language, extended for scripting.
+//!
+//! On top of `forward`, `down` and `up`, a script can use:
+//!
+//! * `back N`, which moves the opposite way to `forward N`,
+//! * `turn`, which turns the submarine around so `forward` and `back` swap directions,
+//! * `repeat N { ... }`, which runs the enclosed statements `N` times (and can be nested),
+//! * `#` comments, which run to the end of the line.
+//!
+//! ```text
+//! # Zig-zag down, then come back
+//! repeat 3 {
+//!     forward 5
+//!     down 2
+//! }
+//! turn
+//! forward 15
+//! ```
+
+use crate::Command;
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Statement {
+    Forward(i64),
+    Back(i64),
+    Down(i64),
+    Up(i64),
+    Turn,
+    Repeat(u32, Vec<Statement>),
+}
+
+pub fn parse(input: &str) -> Result<Vec<Statement>, ScriptError> {
+    let mut tokens = tokenize(input).into_iter().peekable();
+    let statements = parse_block(&mut tokens, None)?;
+    Ok(statements)
+}
+
+/// Runs the script in the submarine's own terms, passing each command to `visit` as it's reached:
+/// `back` and `turn` become signed `forward`s, and `repeat` bodies are run over again rather than
+/// unrolled, so large counts take time but no extra memory. Stops at the first error from `visit`.
+pub fn run<E>(
+    program: &[Statement],
+    visit: &mut impl FnMut(Command) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut heading = 1;
+    run_block(program, &mut heading, visit)
+}
+
+fn run_block<E>(
+    program: &[Statement],
+    heading: &mut i64,
+    visit: &mut impl FnMut(Command) -> Result<(), E>,
+) -> Result<(), E> {
+    for statement in program {
+        match statement {
+            Statement::Forward(distance) => visit(Command::Forward(*heading * distance))?,
+            Statement::Back(distance) => visit(Command::Forward(-*heading * distance))?,
+            Statement::Down(distance) => visit(Command::Down(*distance))?,
+            Statement::Up(distance) => visit(Command::Up(*distance))?,
+            Statement::Turn => *heading = -*heading,
+            Statement::Repeat(times, body) => {
+                for _ in 0..*times {
+                    run_block(body, heading, visit)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct Token<'a> {
+    line: usize,
+    text: &'a str,
+}
+
+fn tokenize(input: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        let line_number = idx + 1;
+        let code = match line.split_once('#') {
+            Some((code, _comment)) => code,
+            None => line,
+        };
+        // Braces don't need spaces around them.
+        let mut rest = code;
+        while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+            rest = &rest[start..];
+            let len = if rest.starts_with(['{', '}']) {
+                1
+            } else {
+                rest.find(|c: char| c.is_whitespace() || c == '{' || c == '}')
+                    .unwrap_or(rest.len())
+            };
+            tokens.push(Token {
+                line: line_number,
+                text: &rest[..len],
+            });
+            rest = &rest[len..];
+        }
+    }
+    tokens
+}
+
+type Tokens<'a> = std::iter::Peekable<std::vec::IntoIter<Token<'a>>>;
+
+/// Parses statements until the end of the input, or the `}` closing a block opened on `opened`.
+fn parse_block(tokens: &mut Tokens, opened: Option<usize>) -> Result<Vec<Statement>, ScriptError> {
+    let mut statements = Vec::new();
+    loop {
+        let token = match tokens.next() {
+            Some(token) => token,
+            None => {
+                return match opened {
+                    Some(line) => Err(ScriptError::UnclosedBlock { line }),
+                    None => Ok(statements),
+                }
+            }
+        };
+
+        let statement = match token.text {
+            "}" if opened.is_some() => return Ok(statements),
+            "forward" => Statement::Forward(distance(tokens, &token)?),
+            "back" => Statement::Back(distance(tokens, &token)?),
+            "down" => Statement::Down(distance(tokens, &token)?),
+            "up" => Statement::Up(distance(tokens, &token)?),
+            "turn" => Statement::Turn,
+            "repeat" => {
+                let times = number(tokens, &token)?;
+                match tokens.next() {
+                    Some(Token { text: "{", .. }) => {}
+                    _ => return Err(ScriptError::ExpectedBlock { line: token.line }),
+                }
+                Statement::Repeat(times, parse_block(tokens, Some(token.line))?)
+            }
+            text => {
+                return Err(ScriptError::UnknownCommand {
+                    line: token.line,
+                    command: text.to_string(),
+                })
+            }
+        };
+        statements.push(statement);
+    }
+}
+
+fn distance(tokens: &mut Tokens, command: &Token) -> Result<i64, ScriptError> {
+    number(tokens, command)
+}
+
+fn number<T: std::str::FromStr>(tokens: &mut Tokens, command: &Token) -> Result<T, ScriptError> {
+    let token = match tokens.next_if(|token| token.line == command.line) {
+        Some(token) => token,
+        None => {
+            return Err(ScriptError::MissingNumber {
+                line: command.line,
+                command: command.text.to_string(),
+            })
+        }
+    };
+    token.text.parse().map_err(|_| ScriptError::InvalidNumber {
+        line: token.line,
+        value: token.text.to_string(),
+    })
+}
+
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum ScriptError {
+    #[error("Line {line}: unrecognized command '{command}'")]
+    UnknownCommand { line: usize, command: String },
+
+    #[error("Line {line}: '{command}' needs a number")]
+    MissingNumber { line: usize, command: String },
+
+    #[error("Line {line}: '{value}' is not a valid number")]
+    InvalidNumber { line: usize, value: String },
+
+    #[error("Line {line}: expected '{{' after repeat")]
+    ExpectedBlock { line: usize },
+
+    #[error("Line {line}: repeat block is never closed")]
+    UnclosedBlock { line: usize },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Submarine;
+
+    fn commands(program: &[Statement]) -> Vec<Command> {
+        let mut commands = Vec::new();
+        run(program, &mut |command| -> Result<(), ()> {
+            commands.push(command);
+            Ok(())
+        })
+        .unwrap();
+        commands
+    }
+
+    #[test]
+    fn plain_commands() {
+        let program = parse("forward 5\ndown 5\nup 3\n").unwrap();
+        assert_eq!(
+            commands(&program),
+            vec![Command::Forward(5), Command::Down(5), Command::Up(3)]
+        );
+    }
+
+    #[test]
+    fn extensions() {
+        let program = parse(
+            "
+            # Zig-zag down, then come back
+            repeat 3 {
+                forward 5 # each leg
+                down 2
+            }
+            turn
+            forward 10
+            back 1
+            repeat 2 { repeat 2 { up 1 } }
+            ",
+        )
+        .unwrap();
+        let commands = commands(&program);
+        assert_eq!(commands.len(), 6 + 2 + 4);
+        assert_eq!(commands[6], Command::Forward(-10));
+        assert_eq!(commands[7], Command::Forward(1));
+
+        let mut position = crate::Position::default();
+        crate::dive(&program, &mut [&mut position], None).unwrap();
+        assert_eq!(position.horizontal_position, 15 - 10 + 1);
+        assert_eq!(position.depth, 6 - 4);
+    }
+
+    #[test]
+    fn large_repeats() {
+        // A billion moves: far too many to unroll.
+        let program = parse("repeat 1000 { repeat 1000 { repeat 1000 { up 1 } } } turn").unwrap();
+        let mut moves = 0u64;
+        let result = run(&program, &mut |_| {
+            moves += 1;
+            if moves == 5000 {
+                Err("stopped")
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(result, Err("stopped"));
+        assert_eq!(moves, 5000);
+
+        let program = parse("repeat 100000 { down 3 up 1 }").unwrap();
+        let mut position = crate::Position::default();
+        crate::dive(&program, &mut [&mut position], None).unwrap();
+        assert_eq!(position.depth(), 200000);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse("forward 1\nsideways 2"),
+            Err(ScriptError::UnknownCommand {
+                line: 2,
+                command: "sideways".to_string()
+            })
+        );
+        assert_eq!(
+            parse("forward\n1"),
+            Err(ScriptError::MissingNumber {
+                line: 1,
+                command: "forward".to_string()
+            })
+        );
+        assert_eq!(
+            parse("down x"),
+            Err(ScriptError::InvalidNumber {
+                line: 1,
+                value: "x".to_string()
+            })
+        );
+        assert_eq!(
+            parse("repeat 2 forward 1"),
+            Err(ScriptError::ExpectedBlock { line: 1 })
+        );
+        assert_eq!(
+            parse("repeat 2 {\nforward 1"),
+            Err(ScriptError::UnclosedBlock { line: 1 })
+        );
+        assert!(parse("}").is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{Command, Submarine};
+use std::io::Write;
+
+/// Writes where each of several submarines is after every command of a run, as CSV.
+pub struct Trajectory<'a> {
+    writer: &'a mut dyn Write,
+    submarines: usize,
+    step: usize,
+}
+
+impl<'a> Trajectory<'a> {
+    /// Starts the CSV with a header naming a pair of columns for each submarine.
+    pub fn new(writer: &'a mut dyn Write, names: &[&str]) -> std::io::Result<Self> {
+        write!(writer, "step,command")?;
+        for name in names {
+            write!(writer, ",{name}_horizontal_position,{name}_depth")?;
+        }
+        writeln!(writer)?;
+        Ok(Self {
+            writer,
+            submarines: names.len(),
+            step: 0,
+        })
+    }
+
+    pub fn record(
+        &mut self,
+        command: &Command,
+        submarines: &[&mut dyn Submarine],
+    ) -> std::io::Result<()> {
+        assert_eq!(submarines.len(), self.submarines, "One submarine per name");
+        self.step += 1;
+        write!(self.writer, "{},{}", self.step, command)?;
+        for submarine in submarines {
+            write!(
+                self.writer,
+                ",{},{}",
+                submarine.horizontal_position(),
+                submarine.depth()
+            )?;
+        }
+        writeln!(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Position, Position2};
+
+    #[test]
+    fn csv() -> crate::Result<()> {
+        let program = crate::parse("forward 5\ndown 5\nforward 8")?;
+        let mut csv = Vec::new();
+        let mut trajectory = Trajectory::new(&mut csv, &["part_one", "part_two"])?;
+        let mut position = Position::default();
+        let mut position2 = Position2::default();
+        crate::dive(
+            &program,
+            &mut [&mut position, &mut position2],
+            Some(&mut trajectory),
+        )?;
+        assert_eq!(position2.depth, 40);
+
+        assert_eq!(
+            String::from_utf8(csv)?,
+            "step,command,part_one_horizontal_position,part_one_depth,\
+             part_two_horizontal_position,part_two_depth\n\
+             1,forward 5,5,0,5,0\n\
+             2,down 5,5,5,5,0\n\
+             3,forward 8,13,5,13,40\n"
+        );
+
+        Ok(())
+    }
+}
@@ -1,87 +1,160 @@
+mod reading;
+mod sorted;
+
+use reading::{Reading, Readings};
+
 type Result<T, E = Box<dyn std::error::Error + Send + Sync>> = std::result::Result<T, E>;
 
 fn main() -> Result<()> {
-    let str = include_str!("input.txt");
-    let parsed = parse(str)?;
-    let mut evaluator = DiagnosticReporter::<12>::default();
-    for input in &parsed {
+    let str = match std::env::args().nth(1).as_deref() {
+        Some("--bench") => {
+            let lines = match std::env::args().nth(2) {
+                Some(lines) => lines.parse()?,
+                None => 1_000_000,
+            };
+            bench(lines);
+            return Ok(());
+        }
+        Some(path) => std::fs::read_to_string(path)?,
+        None => include_str!("input.txt").to_string(),
+    };
+
+    // Pick the narrowest integer that fits the readings.
+    match reading::width(&str) {
+        0..=16 => report::<u16>(&str),
+        17..=32 => report::<u32>(&str),
+        33..=64 => report::<u64>(&str),
+        _ => report::<u128>(&str),
+    }
+}
+
+fn report<T: Reading>(str: &str) -> Result<()> {
+    let parsed = reading::parse::<T>(str)?;
+    let mut evaluator = DiagnosticReporter::new(parsed.width);
+    for input in &parsed.values {
         evaluator.evaluate(*input);
     }
     let gamma = evaluator.gamma_rate();
     let epsilon = evaluator.epsilon_rate();
     println!(
         "{} • gamma={} • epsilon={}",
-        gamma as u64 * epsilon as u64,
+        product(gamma, epsilon),
         gamma,
         epsilon
     );
 
-    let oxygen = RatingFilter::find_rating(RatingFilterStrategy::Oxygen, parsed.clone(), 12);
-    let co2 = RatingFilter::find_rating(RatingFilterStrategy::Co2, parsed, 12);
-    println!(
-        "{} • oxygen={} • co2={}",
-        oxygen as u64 * co2 as u64,
-        oxygen,
-        co2
-    );
+    let oxygen = RatingFilter::find_rating(RatingFilterStrategy::Oxygen, &parsed);
+    let co2 = RatingFilter::find_rating(RatingFilterStrategy::Co2, &parsed);
+    println!("{} • oxygen={} • co2={}", product(oxygen, co2), oxygen, co2);
 
     Ok(())
 }
 
-fn parse(input: &str) -> Result<Vec<u16>> {
-    input
-        .lines()
-        .map(|line| u16::from_str_radix(line, 2).map_err(Into::into))
-        .collect::<Result<_>>()
+/// Times both rating searches on random 24-bit readings.
+fn bench(lines: usize) {
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    let readings = random_readings::<u32>(&mut rng, 24, lines);
+
+    let start = std::time::Instant::now();
+    let sorted = sorted::SortedRatings::new(&readings);
+    println!("sorted {} lines in {:?}", lines, start.elapsed());
+
+    for strategy in [RatingFilterStrategy::Oxygen, RatingFilterStrategy::Co2] {
+        let start = std::time::Instant::now();
+        let filtered = RatingFilter::try_find_rating(strategy, &readings);
+        let filter_time = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let found = sorted.find_rating(strategy);
+        let search_time = start.elapsed();
+
+        assert_eq!(filtered, found);
+        println!(
+            "{:?}: filter {:?} • sorted search {:?}",
+            strategy, filter_time, search_time
+        );
+    }
+}
+
+/// A small xorshift generator, for repeatable random readings.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+fn random_readings<T: Reading>(rng: &mut XorShift, width: usize, len: usize) -> Readings<T> {
+    let values = (0..len)
+        .map(|_| {
+            let bits = rng.next();
+            (0..width)
+                .filter(|idx| bits >> (idx % 64) & 0b1 == 1)
+                .fold(T::zero(), |v, idx| v.with_bit(idx))
+        })
+        .collect();
+    Readings { width, values }
+}
+
+fn product<T: Reading>(a: T, b: T) -> String {
+    match a.to_u128().checked_mul(b.to_u128()) {
+        Some(product) => product.to_string(),
+        None => "(overflow)".to_string(),
+    }
 }
 
 #[derive(Debug)]
-pub struct DiagnosticReporter<const BITS: usize> {
+pub struct DiagnosticReporter<T = u16> {
     seen: usize,
-    counts: [usize; BITS],
+    counts: Vec<usize>,
+    _reading: std::marker::PhantomData<T>,
 }
 
-impl<const BITS: usize> Default for DiagnosticReporter<BITS> {
-    fn default() -> Self {
+impl<T: Reading> DiagnosticReporter<T> {
+    pub fn new(width: usize) -> Self {
+        assert!(width <= T::BITS);
+
         let seen = 0;
-        let counts = [0; BITS];
-        Self { seen, counts }
+        let counts = vec![0; width];
+        Self {
+            seen,
+            counts,
+            _reading: std::marker::PhantomData,
+        }
     }
-}
-
-impl<const BITS: usize> DiagnosticReporter<BITS> {
-    fn mask() -> u16 {
-        assert!(BITS < u16::BITS as usize);
 
-        (1 << BITS) - 1
+    fn mask(&self) -> T {
+        T::mask(self.counts.len())
     }
 
-    pub fn evaluate(&mut self, input: u16) {
-        for idx in 0..BITS {
-            let mask = 1_u16 << idx;
-            let masked = input & mask;
-            let shifted = masked >> idx;
-            self.counts[idx] += shifted as usize;
+    pub fn evaluate(&mut self, input: T) {
+        for (idx, count) in self.counts.iter_mut().enumerate() {
+            *count += input.bit(idx) as usize;
         }
         self.seen += 1;
     }
 
-    pub fn gamma_rate(&self) -> u16 {
+    pub fn gamma_rate(&self) -> T {
         let half = self.seen / 2;
-        let mut result = 0;
+        let mut result = T::zero();
 
-        for idx in 0..BITS {
-            let v = if self.counts[idx] > half { 1 } else { 0 };
-            result |= v << idx;
+        for (idx, count) in self.counts.iter().enumerate() {
+            if *count > half {
+                result = result.with_bit(idx);
+            }
         }
 
         result
     }
 
-    pub fn epsilon_rate(&self) -> u16 {
+    pub fn epsilon_rate(&self) -> T {
         let gamma = self.gamma_rate();
-        let epsilon_unmasked = !gamma;
-        epsilon_unmasked & Self::mask()
+        let epsilon_unmasked = gamma.invert();
+        epsilon_unmasked.and(self.mask())
     }
 }
 
@@ -111,7 +184,7 @@ impl RatingFilter {
         Self { strategy }
     }
 
-    pub fn filter(&self, idx: usize, items: Vec<u16>) -> Vec<u16> {
+    pub fn filter<T: Reading>(&self, idx: usize, items: Vec<T>) -> Vec<T> {
         let len = items.len();
         if len <= 1 {
             return items;
@@ -120,21 +193,28 @@ impl RatingFilter {
         let mut ones = 0;
 
         for item in &items {
-            let item = *item;
-            let is_one = (item >> idx) & 0b1;
-            ones += is_one as usize;
+            ones += item.bit(idx) as usize;
         }
 
         let keep = self.strategy.keep(ones, len - ones);
 
-        let is_match = |v: &u16| -> bool { v >> idx & 0b1 == keep };
+        let is_match = |v: &T| -> bool { v.bit(idx) as u16 == keep };
 
         items.into_iter().filter(is_match).collect()
     }
 
-    pub fn find_rating(strategy: RatingFilterStrategy, mut items: Vec<u16>, bits: usize) -> u16 {
-        let mut idx = bits - 1;
-        assert!(!items.is_empty());
+    pub fn find_rating<T: Reading>(strategy: RatingFilterStrategy, readings: &Readings<T>) -> T {
+        Self::try_find_rating(strategy, readings).expect("Not found")
+    }
+
+    /// Like `find_rating`, but returns `None` if the filter runs out of readings or bits before
+    /// narrowing down to one.
+    pub fn try_find_rating<T: Reading>(
+        strategy: RatingFilterStrategy,
+        readings: &Readings<T>,
+    ) -> Option<T> {
+        let mut items = readings.values.clone();
+        let mut idx = readings.width - 1;
         let filter = RatingFilter::new(strategy);
 
         while items.len() > 1 {
@@ -145,13 +225,12 @@ impl RatingFilter {
             }
 
             if idx == 0 {
-                panic!("Not found");
+                return None;
             }
             idx -= 1;
         }
 
-        assert!(!items.is_empty());
-        items[0]
+        items.first().copied()
     }
 }
 
@@ -161,19 +240,20 @@ mod test {
 
     #[test]
     fn mask() {
-        assert_eq!(DiagnosticReporter::<1>::mask(), 0b1);
-        assert_eq!(DiagnosticReporter::<2>::mask(), 0b11);
-        assert_eq!(DiagnosticReporter::<3>::mask(), 0b111);
-        assert_eq!(DiagnosticReporter::<5>::mask(), 0b11111);
-        assert_eq!(DiagnosticReporter::<12>::mask(), 0b111111111111);
+        assert_eq!(DiagnosticReporter::<u16>::new(1).mask(), 0b1);
+        assert_eq!(DiagnosticReporter::<u16>::new(2).mask(), 0b11);
+        assert_eq!(DiagnosticReporter::<u16>::new(3).mask(), 0b111);
+        assert_eq!(DiagnosticReporter::<u16>::new(5).mask(), 0b11111);
+        assert_eq!(DiagnosticReporter::<u16>::new(12).mask(), 0b111111111111);
+        assert_eq!(DiagnosticReporter::<u16>::new(16).mask(), u16::MAX);
     }
 
     #[test]
     fn test_1() -> Result<()> {
         let str = include_str!("example.txt");
-        let parsed = parse(str)?;
-        let mut evaluator = DiagnosticReporter::<5>::default();
-        for input in &parsed {
+        let parsed = reading::parse::<u16>(str)?;
+        let mut evaluator = DiagnosticReporter::new(parsed.width);
+        for input in &parsed.values {
             evaluator.evaluate(*input);
         }
 
@@ -217,13 +297,39 @@ mod test {
     #[test]
     fn test_2() -> Result<()> {
         let str = include_str!("example.txt");
-        let parsed = parse(str)?;
-        let oxygen = RatingFilter::find_rating(RatingFilterStrategy::Oxygen, parsed.clone(), 5);
+        let parsed = reading::parse::<u16>(str)?;
+        let oxygen = RatingFilter::find_rating(RatingFilterStrategy::Oxygen, &parsed);
         assert_eq!(oxygen, 0b10111);
 
-        let co2 = RatingFilter::find_rating(RatingFilterStrategy::Co2, parsed.clone(), 5);
+        let co2 = RatingFilter::find_rating(RatingFilterStrategy::Co2, &parsed);
         assert_eq!(co2, 0b01010);
 
         Ok(())
     }
+
+    #[test]
+    fn wide_readings() -> Result<()> {
+        // The example, with every reading repeated 20 times over.
+        let str = include_str!("example.txt")
+            .lines()
+            .map(|line| format!("{}\n", line.repeat(20)))
+            .collect::<String>();
+        let parsed = reading::parse::<u128>(&str)?;
+        assert_eq!(parsed.width, 100);
+        let repeated = |v: u128| (0..20).map(|i| v << (5 * i)).sum::<u128>();
+
+        let mut evaluator = DiagnosticReporter::new(parsed.width);
+        for input in &parsed.values {
+            evaluator.evaluate(*input);
+        }
+        assert_eq!(evaluator.gamma_rate(), repeated(22));
+        assert_eq!(evaluator.epsilon_rate(), repeated(9));
+
+        let oxygen = RatingFilter::find_rating(RatingFilterStrategy::Oxygen, &parsed);
+        assert_eq!(oxygen, repeated(0b10111));
+        let co2 = RatingFilter::find_rating(RatingFilterStrategy::Co2, &parsed);
+        assert_eq!(co2, repeated(0b01010));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,198 @@
+use std::fmt::{Debug, Display};
+
+/// An integer type that can hold one diagnostic reading, one bit per column.
+pub trait Reading: Copy + Eq + Ord + Debug + Display + Send + Sync {
+    /// The widest reading this type can hold.
+    const BITS: usize;
+
+    fn zero() -> Self;
+
+    /// The lowest `width` bits set.
+    fn mask(width: usize) -> Self;
+
+    fn bit(self, idx: usize) -> bool;
+
+    fn with_bit(self, idx: usize) -> Self;
+
+    fn invert(self) -> Self;
+
+    fn and(self, other: Self) -> Self;
+
+    fn to_u128(self) -> u128;
+}
+
+macro_rules! impl_reading {
+    ($($t:ty),*) => {
+        $(
+            impl Reading for $t {
+                const BITS: usize = <$t>::BITS as usize;
+
+                fn zero() -> Self {
+                    0
+                }
+
+                fn mask(width: usize) -> Self {
+                    assert!(width <= <Self as Reading>::BITS);
+                    if width == <Self as Reading>::BITS {
+                        <$t>::MAX
+                    } else {
+                        (1 << width) - 1
+                    }
+                }
+
+                fn bit(self, idx: usize) -> bool {
+                    (self >> idx) & 0b1 == 1
+                }
+
+                fn with_bit(self, idx: usize) -> Self {
+                    self | (1 << idx)
+                }
+
+                fn invert(self) -> Self {
+                    !self
+                }
+
+                fn and(self, other: Self) -> Self {
+                    self & other
+                }
+
+                fn to_u128(self) -> u128 {
+                    self as u128
+                }
+            }
+        )*
+    };
+}
+
+impl_reading!(u16, u32, u64, u128);
+
+/// Readings parsed from the input, along with how many bits wide each line was.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Readings<T> {
+    pub width: usize,
+    pub values: Vec<T>,
+}
+
+pub fn parse<T: Reading>(input: &str) -> Result<Readings<T>, ParseError> {
+    let mut width = None;
+    let mut values = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        let line_number = idx + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match width {
+            None if line.len() > T::BITS => {
+                return Err(ParseError::TooWide {
+                    width: line.len(),
+                    max: T::BITS,
+                })
+            }
+            None => width = Some(line.len()),
+            Some(width) if width != line.len() => {
+                return Err(ParseError::InconsistentWidth {
+                    line: line_number,
+                    expected: width,
+                    found: line.len(),
+                })
+            }
+            Some(_) => {}
+        }
+
+        let mut value = T::zero();
+        for (idx, c) in line.chars().rev().enumerate() {
+            match c {
+                '0' => {}
+                '1' => value = value.with_bit(idx),
+                c => {
+                    return Err(ParseError::InvalidDigit {
+                        line: line_number,
+                        digit: c,
+                    })
+                }
+            }
+        }
+        values.push(value);
+    }
+
+    let width = width.ok_or(ParseError::Empty)?;
+    Ok(Readings { width, values })
+}
+
+/// The width of the first non-empty line of `input`.
+pub fn width(input: &str) -> usize {
+    input
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map_or(0, str::len)
+}
+
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum ParseError {
+    #[error("No readings")]
+    Empty,
+
+    #[error("Line {line}: invalid digit '{digit}'")]
+    InvalidDigit { line: usize, digit: char },
+
+    #[error("Line {line}: expected {expected} bits, found {found}")]
+    InconsistentWidth {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("Readings are {width} bits wide, but only {max} fit")]
+    TooWide { width: usize, max: usize },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mask() {
+        assert_eq!(u16::mask(5), 0b11111);
+        assert_eq!(u16::mask(16), u16::MAX);
+        assert_eq!(u128::mask(100), (1 << 100) - 1);
+    }
+
+    #[test]
+    fn parse_widths() {
+        let readings = parse::<u16>("00100\n11110\n").unwrap();
+        assert_eq!(readings.width, 5);
+        assert_eq!(readings.values, vec![0b00100, 0b11110]);
+
+        let line = "1".repeat(100);
+        let readings = parse::<u128>(&line).unwrap();
+        assert_eq!(readings.width, 100);
+        assert_eq!(readings.values, vec![u128::mask(100)]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse::<u16>(""), Err(ParseError::Empty));
+        assert_eq!(
+            parse::<u16>("0101\n012\n").unwrap_err(),
+            ParseError::InconsistentWidth {
+                line: 2,
+                expected: 4,
+                found: 3
+            }
+        );
+        assert_eq!(
+            parse::<u16>("0101\n0121\n").unwrap_err(),
+            ParseError::InvalidDigit {
+                line: 2,
+                digit: '2'
+            }
+        );
+        assert_eq!(
+            parse::<u16>(&"1".repeat(17)).unwrap_err(),
+            ParseError::TooWide { width: 17, max: 16 }
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! Finds the oxygen and CO2 ratings by sorting the readings once and narrowing a range of them.
+//!
+//! Once sorted, the readings that share the bits picked so far are a contiguous range, and within
+//! that range the ones with a `0` in the next bit all come before the ones with a `1`. Each step
+//! only has to find where that split is, rather than re-scanning and re-collecting every reading.
+
+use crate::reading::{Reading, Readings};
+use crate::RatingFilterStrategy;
+
+pub struct SortedRatings<T> {
+    width: usize,
+    values: Vec<T>,
+}
+
+impl<T: Reading> SortedRatings<T> {
+    pub fn new(readings: &Readings<T>) -> Self {
+        let mut values = readings.values.clone();
+        values.sort_unstable();
+        Self {
+            width: readings.width,
+            values,
+        }
+    }
+
+    /// The same rating `RatingFilter::try_find_rating` finds, in `O(bits * log n)`.
+    pub fn find_rating(&self, strategy: RatingFilterStrategy) -> Option<T> {
+        let mut range = &self.values[..];
+        for idx in (0..self.width).rev() {
+            if range.len() <= 1 {
+                break;
+            }
+            let split = range.partition_point(|v| !v.bit(idx));
+            let (zeros, ones) = range.split_at(split);
+            range = match strategy.keep(ones.len(), zeros.len()) {
+                0 => zeros,
+                _ => ones,
+            };
+        }
+
+        match range {
+            [rating] => Some(*rating),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::reading::parse;
+    use crate::RatingFilter;
+
+    #[test]
+    fn example() {
+        let readings = parse::<u16>(include_str!("example.txt")).unwrap();
+        let sorted = SortedRatings::new(&readings);
+        assert_eq!(
+            sorted.find_rating(RatingFilterStrategy::Oxygen),
+            Some(0b10111)
+        );
+        assert_eq!(sorted.find_rating(RatingFilterStrategy::Co2), Some(0b01010));
+    }
+
+    #[test]
+    fn matches_filter() {
+        let mut rng = crate::XorShift(0x2545_f491_4f6c_dd1d);
+        for width in [1, 3, 5, 12, 40] {
+            for len in [1, 2, 3, 10, 100, 1000] {
+                let readings = crate::random_readings::<u64>(&mut rng, width, len);
+                let sorted = SortedRatings::new(&readings);
+                for strategy in [RatingFilterStrategy::Oxygen, RatingFilterStrategy::Co2] {
+                    assert_eq!(
+                        sorted.find_rating(strategy),
+                        RatingFilter::try_find_rating(strategy, &readings),
+                        "{:?} {:?}",
+                        strategy,
+                        readings
+                    );
+                }
+            }
+        }
+    }
+}
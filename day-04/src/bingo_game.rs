@@ -1,49 +1,63 @@
 use crate::bingo_board::BingoBoard;
 use crate::coordinate::Coordinate;
+use crate::win_rule::WinRule;
 
 #[derive(Copy, Clone)]
-pub struct BingoGame<'a> {
-    board: &'a BingoBoard,
-    chips: Chips,
+pub struct BingoGame<'a, const N: usize = 5> {
+    board: &'a BingoBoard<N>,
+    rule: WinRule<'a, N>,
+    chips: Chips<N>,
 }
 
-impl<'a> BingoGame<'a> {
-    pub const fn new(board: &BingoBoard) -> BingoGame {
+impl<'a, const N: usize> BingoGame<'a, N> {
+    pub const fn new(board: &BingoBoard<N>) -> BingoGame<'_, N> {
         BingoGame {
             board,
+            rule: WinRule::Lines,
             chips: Chips::new(),
         }
     }
 
-    pub fn call(&mut self, val: u8) {
-        if let Some(coordinate) = self.board.find(val) {
+    pub const fn with_rule(mut self, rule: WinRule<'a, N>) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    /// Places a chip on `val`, returning where it went if the board has it.
+    pub const fn call(&mut self, val: u8) -> Option<Coordinate<N>> {
+        let coordinate = self.board.find(val);
+        if let Some(coordinate) = coordinate {
             self.chips.set_called(coordinate);
         }
+        coordinate
     }
 
     pub const fn with_called(mut self, val: u8) -> Self {
-        if let Some(coordinate) = self.board.find(val) {
-            self.chips = self.chips.called(coordinate);
-        }
+        self.call(val);
         self
     }
 
     pub const fn has_won(&self) -> bool {
-        let mut i = 0;
-        let len = WINNING_POSITIONS.len();
-        while i < len {
-            if self.chips.contains(&WINNING_POSITIONS[i]) {
-                return true;
-            }
-            i += 1;
-        }
-        false
+        self.rule.is_won(&self.chips)
+    }
+
+    /// The chips that won the game, if it has been won.
+    pub const fn winning_mask(&self) -> Option<Chips<N>> {
+        self.rule.winning_mask(&self.chips)
+    }
+
+    pub const fn board(&self) -> &'a BingoBoard<N> {
+        self.board
+    }
+
+    pub const fn is_called(&self, coordinate: Coordinate<N>) -> bool {
+        self.chips.is_called(coordinate)
     }
 
     pub const fn unmarked_sum(&self) -> u16 {
         let mut sum = 0;
         let mut i = 0;
-        while i < 25 {
+        while i < N * N {
             let coordinate = Coordinate::from_index(i);
             if !self.chips.is_called(coordinate) {
                 sum += self.board.at(coordinate) as u16;
@@ -54,7 +68,8 @@ impl<'a> BingoGame<'a> {
         sum
     }
 
-    pub const fn play(self, calls: &[u8]) -> Endgame {
+    /// Plays until the board wins, or returns `None` if it never does.
+    pub const fn play(self, calls: &[u8]) -> Option<Endgame> {
         let mut this = self;
         let mut i = 0;
         let len = calls.len();
@@ -62,129 +77,97 @@ impl<'a> BingoGame<'a> {
             let call = calls[i];
             this = this.with_called(call);
             if this.has_won() {
-                return Endgame {
+                return Some(Endgame {
                     turns: i + 1,
                     last_call: call,
                     unmarked_sum: this.unmarked_sum(),
-                };
+                });
             }
             i += 1;
         }
 
-        panic!("Did not finish");
+        None
     }
 }
 
+#[derive(Copy, Clone, Debug)]
 pub struct Endgame {
     pub turns: usize,
     pub last_call: u8,
     pub unmarked_sum: u16,
 }
 
-#[derive(Copy, Clone, Default)]
+impl Endgame {
+    pub const fn score(&self) -> usize {
+        self.last_call as usize * self.unmarked_sum as usize
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// In a physical game of bingo, chips on a board are used to represent which numbers
 /// have been called.
 ///
 /// This `Chips` struct does the same thing: it tracks which numbers on the board have
 /// been called, by tracking the positions of those chips.
-struct Chips(u32);
+///
+/// The chips are kept in a `u128`, which fits boards up to 11x11. (A narrower integer for
+/// smaller boards would need the width to be picked by a trait, and trait methods can't be
+/// called from `const fn`.)
+pub struct Chips<const N: usize>(u128);
+
+impl<const N: usize> Chips<N> {
+    /// No chips placed. Evaluating this makes oversized boards a compile error.
+    const EMPTY: u128 = {
+        assert!(N * N <= u128::BITS as usize, "Board is too large");
+        0
+    };
+
+    /// Every complete row, as the chips needed to win with it.
+    pub(crate) const ROWS: [Chips<N>; N] = Self::lines(true);
+
+    /// Every complete column, as the chips needed to win with it.
+    pub(crate) const COLUMNS: [Chips<N>; N] = Self::lines(false);
 
-impl Chips {
     pub const fn new() -> Self {
-        Self(0)
+        Self(Self::EMPTY)
     }
 
-    pub const fn called(mut self, coordinate: Coordinate) -> Self {
+    pub const fn called(mut self, coordinate: Coordinate<N>) -> Self {
         self.0 |= 1 << coordinate.index();
         self
     }
 
-    pub fn set_called(&mut self, coordinate: Coordinate) {
+    pub const fn set_called(&mut self, coordinate: Coordinate<N>) {
         self.0 |= 1 << coordinate.index();
     }
 
-    pub const fn is_called(&self, coordinate: Coordinate) -> bool {
+    pub const fn is_called(&self, coordinate: Coordinate<N>) -> bool {
         (self.0 >> coordinate.index() & 0b1) == 1
     }
 
-    pub const fn contains(&self, other: &Chips) -> bool {
+    pub const fn contains(&self, other: &Chips<N>) -> bool {
         self.0 & other.0 == other.0
     }
-}
 
-const WINNING_POSITIONS: &[Chips] = &[
-    WINNING_POSITION_H0,
-    WINNING_POSITION_H1,
-    WINNING_POSITION_H2,
-    WINNING_POSITION_H3,
-    WINNING_POSITION_H4,
-    WINNING_POSITION_V0,
-    WINNING_POSITION_V1,
-    WINNING_POSITION_V2,
-    WINNING_POSITION_V3,
-    WINNING_POSITION_V4,
-];
-
-const WINNING_POSITION_H0: Chips = Chips::new()
-    .called(Coordinate::from_pos(0, 0))
-    .called(Coordinate::from_pos(1, 0))
-    .called(Coordinate::from_pos(2, 0))
-    .called(Coordinate::from_pos(3, 0))
-    .called(Coordinate::from_pos(4, 0));
-const WINNING_POSITION_H1: Chips = Chips::new()
-    .called(Coordinate::from_pos(0, 1))
-    .called(Coordinate::from_pos(1, 1))
-    .called(Coordinate::from_pos(2, 1))
-    .called(Coordinate::from_pos(3, 1))
-    .called(Coordinate::from_pos(4, 1));
-const WINNING_POSITION_H2: Chips = Chips::new()
-    .called(Coordinate::from_pos(0, 2))
-    .called(Coordinate::from_pos(1, 2))
-    .called(Coordinate::from_pos(2, 2))
-    .called(Coordinate::from_pos(3, 2))
-    .called(Coordinate::from_pos(4, 2));
-const WINNING_POSITION_H3: Chips = Chips::new()
-    .called(Coordinate::from_pos(0, 3))
-    .called(Coordinate::from_pos(1, 3))
-    .called(Coordinate::from_pos(2, 3))
-    .called(Coordinate::from_pos(3, 3))
-    .called(Coordinate::from_pos(4, 3));
-const WINNING_POSITION_H4: Chips = Chips::new()
-    .called(Coordinate::from_pos(0, 4))
-    .called(Coordinate::from_pos(1, 4))
-    .called(Coordinate::from_pos(2, 4))
-    .called(Coordinate::from_pos(3, 4))
-    .called(Coordinate::from_pos(4, 4));
-const WINNING_POSITION_V0: Chips = Chips::new()
-    .called(Coordinate::from_pos(0, 0))
-    .called(Coordinate::from_pos(0, 1))
-    .called(Coordinate::from_pos(0, 2))
-    .called(Coordinate::from_pos(0, 3))
-    .called(Coordinate::from_pos(0, 4));
-const WINNING_POSITION_V1: Chips = Chips::new()
-    .called(Coordinate::from_pos(1, 0))
-    .called(Coordinate::from_pos(1, 1))
-    .called(Coordinate::from_pos(1, 2))
-    .called(Coordinate::from_pos(1, 3))
-    .called(Coordinate::from_pos(1, 4));
-const WINNING_POSITION_V2: Chips = Chips::new()
-    .called(Coordinate::from_pos(2, 0))
-    .called(Coordinate::from_pos(2, 1))
-    .called(Coordinate::from_pos(2, 2))
-    .called(Coordinate::from_pos(2, 3))
-    .called(Coordinate::from_pos(2, 4));
-const WINNING_POSITION_V3: Chips = Chips::new()
-    .called(Coordinate::from_pos(3, 0))
-    .called(Coordinate::from_pos(3, 1))
-    .called(Coordinate::from_pos(3, 2))
-    .called(Coordinate::from_pos(3, 3))
-    .called(Coordinate::from_pos(3, 4));
-const WINNING_POSITION_V4: Chips = Chips::new()
-    .called(Coordinate::from_pos(4, 0))
-    .called(Coordinate::from_pos(4, 1))
-    .called(Coordinate::from_pos(4, 2))
-    .called(Coordinate::from_pos(4, 3))
-    .called(Coordinate::from_pos(4, 4));
+    pub const fn union(self, other: Chips<N>) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    const fn lines(rows: bool) -> [Chips<N>; N] {
+        let mut lines = [Chips(0); N];
+        let mut line = 0;
+        while line < N {
+            let mut i = 0;
+            while i < N {
+                let (x, y) = if rows { (i, line) } else { (line, i) };
+                lines[line] = lines[line].called(Coordinate::from_pos(x as u8, y as u8));
+                i += 1;
+            }
+            line += 1;
+        }
+        lines
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -192,7 +175,7 @@ mod tests {
 
     #[test]
     fn chips() {
-        let mut mask = Chips::new();
+        let mut mask: Chips<5> = Chips::new();
         mask.set_called(Coordinate::from_pos(2, 2));
         mask.set_called(Coordinate::from_pos(1, 3));
 
@@ -214,13 +197,13 @@ mod tests {
             (1, 2),
         ];
 
-        let mut chips = Chips::new();
+        let mut chips: Chips<5> = Chips::new();
         for (x, y) in called {
             chips.set_called(Coordinate::from_pos(x, y));
         }
 
-        assert!(chips.contains(&WINNING_POSITION_V0));
-        assert!(!chips.contains(&WINNING_POSITION_V1));
+        assert!(chips.contains(&Chips::COLUMNS[0]));
+        assert!(!chips.contains(&Chips::COLUMNS[1]));
     }
 
     #[test]
@@ -239,10 +222,42 @@ mod tests {
         }
         assert!(game.has_won());
 
-        let mut game = BingoGame::new(&board);
-        let endgame = game.play(&calls);
+        let game = BingoGame::new(&board);
+        let endgame = game.play(&calls).unwrap();
         assert_eq!(endgame.turns, 12);
         assert_eq!(endgame.last_call, 24);
         assert_eq!(endgame.unmarked_sum, 188);
+        assert_eq!(endgame.score(), 4512);
+
+        assert!(BingoGame::new(&board).play(&calls[..11]).is_none());
+    }
+
+    #[test]
+    fn winning_lines() {
+        let row = Chips::<5>::ROWS[2];
+        for x in 0..5 {
+            assert!(row.is_called(Coordinate::from_pos(x, 2)));
+            assert!(!row.is_called(Coordinate::from_pos(x, 1)));
+        }
+        let column = Chips::<3>::COLUMNS[1];
+        assert_eq!(column.0, 0b010_010_010);
+    }
+
+    #[test]
+    fn other_sizes() {
+        let board = BingoBoard::from_arrays([[1, 2, 3], [4, 5, 6], [7, 8, 9]]);
+        let endgame = BingoGame::new(&board).play(&[5, 1, 9, 2, 8]).unwrap();
+        assert_eq!(endgame.turns, 5);
+        assert_eq!(endgame.last_call, 8);
+        assert_eq!(endgame.unmarked_sum, 3 + 4 + 6 + 7);
+
+        let mut rows = [[0; 11]; 11];
+        for (i, value) in rows.iter_mut().flatten().enumerate() {
+            *value = i as u8;
+        }
+        let board = BingoBoard::from_arrays(rows);
+        let calls: Vec<u8> = (0..11).map(|y| y * 11 + 10).collect();
+        let endgame = BingoGame::new(&board).play(&calls).unwrap();
+        assert_eq!(endgame.turns, 11);
     }
 }
@@ -0,0 +1,235 @@
+//! Plays every board in a hall at once, one call at a time.
+
+use crate::bingo_board::BingoBoard;
+use crate::bingo_game::BingoGame;
+use crate::coordinate::Coordinate;
+use crate::win_rule::WinRule;
+
+/// Something that happened during a turn.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Event<const N: usize = 5> {
+    /// A board that hasn't won yet had the called number.
+    Marked {
+        board: usize,
+        turn: usize,
+        coordinate: Coordinate<N>,
+    },
+    /// A board won with this call.
+    Won {
+        board: usize,
+        turn: usize,
+        score: usize,
+    },
+    /// The calls ran out before every board had won.
+    Exhausted,
+}
+
+/// A board's place in the finishing order.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Placing {
+    pub board: usize,
+    pub turn: usize,
+    pub score: usize,
+}
+
+pub struct BingoHall<'a, const N: usize = 5> {
+    games: Vec<BingoGame<'a, N>>,
+    won: Vec<bool>,
+    turn: usize,
+}
+
+impl<'a, const N: usize> BingoHall<'a, N> {
+    pub fn new(boards: &'a [BingoBoard<N>]) -> Self {
+        Self {
+            games: boards.iter().map(BingoGame::new).collect(),
+            won: vec![false; boards.len()],
+            turn: 0,
+        }
+    }
+
+    pub fn with_rule(mut self, rule: WinRule<'a, N>) -> Self {
+        self.games = self.games.into_iter().map(|g| g.with_rule(rule)).collect();
+        self
+    }
+
+    pub fn games(&self) -> &[BingoGame<'a, N>] {
+        &self.games
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.won.iter().all(|won| *won)
+    }
+
+    /// Calls `val` on every board that hasn't won yet, returning what happened in board order.
+    pub fn call(&mut self, val: u8) -> Vec<Event<N>> {
+        self.turn += 1;
+        let mut events = Vec::new();
+        for (board, game) in self.games.iter_mut().enumerate() {
+            if self.won[board] {
+                continue;
+            }
+            let coordinate = match game.call(val) {
+                Some(coordinate) => coordinate,
+                None => continue,
+            };
+            events.push(Event::Marked {
+                board,
+                turn: self.turn,
+                coordinate,
+            });
+            if game.has_won() {
+                self.won[board] = true;
+                events.push(Event::Won {
+                    board,
+                    turn: self.turn,
+                    score: val as usize * game.unmarked_sum() as usize,
+                });
+            }
+        }
+        events
+    }
+
+    /// Plays `calls` until every board has won, returning the whole event log. The log ends in
+    /// `Event::Exhausted` if the calls ran out first.
+    pub fn play(mut self, calls: &[u8]) -> Vec<Event<N>> {
+        let mut events = Vec::new();
+        for call in calls {
+            if self.is_finished() {
+                break;
+            }
+            events.extend(self.call(*call));
+        }
+        if !self.is_finished() {
+            events.push(Event::Exhausted);
+        }
+        events
+    }
+
+    /// The order boards won in. Boards that won on the same turn are placed in board order;
+    /// boards that never won are left out.
+    pub fn ranking(self, calls: &[u8]) -> Vec<Placing> {
+        self.play(calls)
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::Won { board, turn, score } => Some(Placing { board, turn, score }),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALLS: &[u8] = &[
+        7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22, 18, 20, 8, 19, 3,
+        26, 1,
+    ];
+
+    fn boards() -> Vec<BingoBoard> {
+        vec![
+            BingoBoard::from_arrays([
+                [22, 13, 17, 11, 0],
+                [8, 2, 23, 4, 24],
+                [21, 9, 14, 16, 7],
+                [6, 10, 3, 18, 5],
+                [1, 12, 20, 15, 19],
+            ]),
+            BingoBoard::from_arrays([
+                [3, 15, 0, 2, 22],
+                [9, 18, 13, 17, 5],
+                [19, 8, 7, 25, 23],
+                [20, 11, 10, 24, 4],
+                [14, 21, 16, 12, 6],
+            ]),
+            BingoBoard::from_arrays([
+                [14, 21, 17, 24, 4],
+                [10, 16, 15, 9, 19],
+                [18, 8, 23, 26, 20],
+                [22, 11, 13, 6, 5],
+                [2, 0, 12, 3, 7],
+            ]),
+        ]
+    }
+
+    #[test]
+    fn example() {
+        let boards = boards();
+        let ranking = BingoHall::new(&boards).ranking(CALLS);
+        assert_eq!(
+            ranking,
+            vec![
+                Placing {
+                    board: 2,
+                    turn: 12,
+                    score: 4512
+                },
+                Placing {
+                    board: 0,
+                    turn: 14,
+                    score: 2192
+                },
+                Placing {
+                    board: 1,
+                    turn: 15,
+                    score: 1924
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn events() {
+        let boards = boards();
+        let mut hall = BingoHall::new(&boards);
+        assert_eq!(
+            hall.call(7),
+            vec![
+                Event::Marked {
+                    board: 0,
+                    turn: 1,
+                    coordinate: Coordinate::from_pos(4, 2)
+                },
+                Event::Marked {
+                    board: 1,
+                    turn: 1,
+                    coordinate: Coordinate::from_pos(2, 2)
+                },
+                Event::Marked {
+                    board: 2,
+                    turn: 1,
+                    coordinate: Coordinate::from_pos(4, 4)
+                },
+            ]
+        );
+        assert_eq!(hall.call(99), vec![]);
+
+        let events = BingoHall::new(&boards).play(&CALLS[..14]);
+        assert_eq!(events.last(), Some(&Event::Exhausted));
+        let won = events
+            .iter()
+            .filter(|event| matches!(event, Event::Won { .. }))
+            .count();
+        assert_eq!(won, 2);
+
+        // Boards that have already won stop being marked.
+        let events = BingoHall::new(&boards).play(CALLS);
+        assert!(!events.contains(&Event::Exhausted));
+        let won_at = events
+            .iter()
+            .position(|event| matches!(event, Event::Won { board: 2, .. }))
+            .unwrap();
+        assert!(!events[won_at..]
+            .iter()
+            .any(|event| matches!(event, Event::Marked { board: 2, .. })));
+    }
+
+    #[test]
+    fn ties() {
+        let boards = [boards()[0], boards()[2], boards()[0]];
+        let ranking = BingoHall::new(&boards).ranking(CALLS);
+        let order: Vec<(usize, usize)> = ranking.iter().map(|p| (p.board, p.turn)).collect();
+        assert_eq!(order, vec![(1, 12), (0, 14), (2, 14)]);
+    }
+}
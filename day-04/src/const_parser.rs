@@ -0,0 +1,273 @@
+//! A `const fn` parser for the puzzle's text format, so the input can be parsed from
+//! `include_bytes!` at compile time:
+//!
+//! ```ignore
+//! const INPUT: &[u8] = include_bytes!("input.txt");
+//! const CALLS: [u8; count_calls(INPUT)] = parse_calls(INPUT);
+//! const BOARDS: [BingoBoard; count_boards(INPUT)] = parse_boards(INPUT);
+//! ```
+//!
+//! Boards can be any size, which is taken from the type they are parsed into.
+//!
+//! Malformed input is reported with `panic!`, which becomes a compile error.
+
+use crate::bingo_board::BingoBoard;
+
+/// The number of calls on the first line.
+pub const fn count_calls(input: &[u8]) -> usize {
+    let mut count = 1;
+    let mut i = 0;
+    while i < input.len() && input[i] != b'\n' {
+        if input[i] == b',' {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+pub const fn parse_calls<const N: usize>(input: &[u8]) -> [u8; N] {
+    let mut calls = [0; N];
+    let mut n = 0;
+    let mut i = 0;
+    loop {
+        let (value, next) = match next_number(input, i) {
+            Some(number) => number,
+            None => panic!("Expected a call"),
+        };
+        if n >= N {
+            panic!("Too many calls");
+        }
+        calls[n] = value;
+        n += 1;
+
+        i = skip_spaces(input, next);
+        if i >= input.len() || input[i] == b'\n' {
+            break;
+        }
+        if input[i] != b',' {
+            panic!("Expected ',' between calls");
+        }
+        i += 1;
+    }
+    if n != N {
+        panic!("Wrong number of calls");
+    }
+    calls
+}
+
+/// The number of boards after the line of calls, which is the number of runs of non-blank lines.
+pub const fn count_boards(input: &[u8]) -> usize {
+    let mut count = 0;
+    let mut in_board = false;
+    let mut i = find_line_end(input, 0);
+    while i < input.len() {
+        let line_end = find_line_end(input, i + 1);
+        let blank = is_blank(input, i + 1, line_end);
+        if !blank && !in_board {
+            count += 1;
+        }
+        in_board = !blank;
+        i = line_end;
+    }
+    count
+}
+
+/// Parses `N` boards of `S`x`S` numbers each.
+pub const fn parse_boards<const N: usize, const S: usize>(input: &[u8]) -> [BingoBoard<S>; N] {
+    let (count, boards) = scan_boards::<N, S>(input);
+    if count != N {
+        panic!("Wrong number of boards");
+    }
+    boards
+}
+
+/// Reads every board, storing the first `N` of them, and returns how many boards there were.
+const fn scan_boards<const N: usize, const S: usize>(input: &[u8]) -> (usize, [BingoBoard<S>; N]) {
+    let mut boards = [BingoBoard::from_arrays([[0; S]; S]); N];
+    let mut count = 0;
+    let mut rows = [[0; S]; S];
+    let mut cells = 0;
+    let mut seen = [false; 256];
+
+    // Skip the line of calls
+    let mut i = 0;
+    while i < input.len() && input[i] != b'\n' {
+        i += 1;
+    }
+
+    while i < input.len() {
+        // `i` is at the start of a line (or the newline ending the previous one)
+        if input[i] == b'\n' {
+            i += 1;
+            continue;
+        }
+        let line_end = find_line_end(input, i);
+        if is_blank(input, i, line_end) {
+            if cells != 0 {
+                panic!("Board has too few rows");
+            }
+            i = line_end;
+            continue;
+        }
+
+        let mut in_row = 0;
+        loop {
+            i = skip_spaces(input, i);
+            if i >= line_end {
+                break;
+            }
+            let (value, next) = match next_number(input, i) {
+                Some(number) => number,
+                None => panic!("Unexpected character in board"),
+            };
+            if in_row == S {
+                panic!("Board row has too many numbers");
+            }
+            if seen[value as usize] {
+                panic!("Board contains a number more than once");
+            }
+            seen[value as usize] = true;
+            rows[cells / S][cells % S] = value;
+            cells += 1;
+            in_row += 1;
+            i = next;
+        }
+        if in_row != S {
+            panic!("Board row has too few numbers");
+        }
+
+        if cells == S * S {
+            if count < N {
+                boards[count] = BingoBoard::from_arrays(rows);
+            }
+            count += 1;
+            cells = 0;
+            seen = [false; 256];
+        }
+    }
+
+    if cells != 0 {
+        panic!("Board has too few rows");
+    }
+    (count, boards)
+}
+
+/// Reads the number starting at `i`, returning it and the index just after it.
+const fn next_number(input: &[u8], i: usize) -> Option<(u8, usize)> {
+    let mut i = skip_spaces(input, i);
+    let start = i;
+    let mut value: u16 = 0;
+    while i < input.len() && input[i].is_ascii_digit() {
+        value = value * 10 + (input[i] - b'0') as u16;
+        if value > u8::MAX as u16 {
+            panic!("Number is too large");
+        }
+        i += 1;
+    }
+    if i == start {
+        None
+    } else {
+        Some((value as u8, i))
+    }
+}
+
+const fn skip_spaces(input: &[u8], mut i: usize) -> usize {
+    while i < input.len() && (input[i] == b' ' || input[i] == b'\r' || input[i] == b'\t') {
+        i += 1;
+    }
+    i
+}
+
+const fn find_line_end(input: &[u8], mut i: usize) -> usize {
+    while i < input.len() && input[i] != b'\n' {
+        i += 1;
+    }
+    i
+}
+
+const fn is_blank(input: &[u8], start: usize, end: usize) -> bool {
+    skip_spaces(input, start) >= end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinate::Coordinate;
+
+    const EXAMPLE: &[u8] = b"7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+";
+
+    const CALLS: [u8; count_calls(EXAMPLE)] = parse_calls(EXAMPLE);
+    const BOARDS: [BingoBoard; count_boards(EXAMPLE)] = parse_boards(EXAMPLE);
+
+    #[test]
+    fn parse_example() {
+        assert_eq!(CALLS.len(), 27);
+        assert_eq!(CALLS[..3], [7, 4, 9]);
+        assert_eq!(CALLS[26], 1);
+        assert_eq!(BOARDS.len(), 3);
+        assert_eq!(BOARDS[0].at(Coordinate::from_pos(0, 1)), 8);
+        assert_eq!(BOARDS[2].at(Coordinate::from_pos(4, 4)), 7);
+
+        const RESULT: usize = crate::solve_a(&CALLS, &BOARDS);
+        assert_eq!(RESULT, 4512);
+    }
+
+    #[test]
+    fn other_sizes() {
+        const INPUT: &[u8] = b"5,1,9,2,3,7,4,6,8
+
+1 2 3
+4 5 6
+7 8 9
+
+10 11 12
+ 5  1  9
+13 14 15
+";
+        const CALLS: [u8; count_calls(INPUT)] = parse_calls(INPUT);
+        const BOARDS: [BingoBoard<3>; count_boards(INPUT)] = parse_boards(INPUT);
+        assert_eq!(BOARDS.len(), 2);
+        assert_eq!(BOARDS[1].at(Coordinate::from_pos(2, 2)), 15);
+
+        // The second board's middle row wins on the third call, and the first board's top row on
+        // the fifth.
+        const RESULT_A: usize = crate::solve_a(&CALLS, &BOARDS);
+        const RESULT_B: usize = crate::solve_b(&CALLS, &BOARDS);
+        assert_eq!(RESULT_A, (10 + 11 + 12 + 13 + 14 + 15) * 9);
+        assert_eq!(RESULT_B, (4 + 6 + 7 + 8) * 3);
+    }
+
+    #[test]
+    fn matches_runtime_parser() {
+        let input = std::str::from_utf8(crate::INPUT).unwrap();
+        let input: crate::parser::BingoInput = input.parse().unwrap();
+        assert_eq!(input.calls, crate::CALLS);
+        assert_eq!(input.boards.len(), crate::BOARDS.len());
+        for (a, b) in input.boards.iter().zip(crate::BOARDS) {
+            for i in 0..25 {
+                let coordinate = Coordinate::from_index(i);
+                assert_eq!(a.at(coordinate), b.at(coordinate));
+            }
+        }
+    }
+}
@@ -1,26 +1,27 @@
 use std::fmt::Debug;
 
+/// A position on an `N`x`N` board.
 #[derive(Copy, Clone, Eq, PartialEq)]
-pub struct Coordinate(usize);
+pub struct Coordinate<const N: usize = 5>(usize);
 
-impl Coordinate {
-    pub const fn from_index(idx: usize) -> Coordinate {
-        if idx >= 25 {
+impl<const N: usize> Coordinate<N> {
+    pub const fn from_index(idx: usize) -> Coordinate<N> {
+        if idx >= N * N {
             panic!("Out of bounds");
         } else {
             Coordinate(idx)
         }
     }
 
-    pub const fn from_tuple((x, y): (u8, u8)) -> Coordinate {
-        if x >= 5 || y >= 5 {
+    pub const fn from_tuple((x, y): (u8, u8)) -> Coordinate<N> {
+        if x as usize >= N || y as usize >= N {
             panic!("Out of bounds");
         } else {
-            Self(x as usize + y as usize * 5)
+            Self(x as usize + y as usize * N)
         }
     }
 
-    pub const fn from_pos(x: u8, y: u8) -> Coordinate {
+    pub const fn from_pos(x: u8, y: u8) -> Coordinate<N> {
         Self::from_tuple((x, y))
     }
 
@@ -29,8 +30,8 @@ impl Coordinate {
     }
 
     pub const fn tuple(self) -> (u8, u8) {
-        let y = self.0 / 5;
-        let x = self.0 - (y * 5);
+        let y = self.0 / N;
+        let x = self.0 - (y * N);
         (x as u8, y as u8)
     }
 
@@ -43,7 +44,7 @@ impl Coordinate {
     }
 }
 
-impl Debug for Coordinate {
+impl<const N: usize> Debug for Coordinate<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Coordinate")
             .field("x", &self.x())
@@ -59,16 +60,16 @@ mod tests {
 
     #[test]
     fn from_index() {
-        let coordinate = Coordinate::from_index(0);
+        let coordinate: Coordinate = Coordinate::from_index(0);
         assert_eq!(coordinate.tuple(), (0, 0));
 
-        let coordinate = Coordinate::from_index(4);
+        let coordinate: Coordinate = Coordinate::from_index(4);
         assert_eq!(coordinate.tuple(), (4, 0));
 
-        let coordinate = Coordinate::from_index(5);
+        let coordinate: Coordinate = Coordinate::from_index(5);
         assert_eq!(coordinate.tuple(), (0, 1));
 
-        let coordinate = Coordinate::from_index(24);
+        let coordinate: Coordinate = Coordinate::from_index(24);
         assert_eq!(coordinate.tuple(), (4, 4));
 
         // let result = Coordinate::from_const_index(25);
@@ -77,19 +78,19 @@ mod tests {
 
     #[test]
     fn from_tuple() {
-        let coordinate = Coordinate::from_tuple((0, 0));
+        let coordinate: Coordinate = Coordinate::from_tuple((0, 0));
         assert_eq!(coordinate.tuple(), (0, 0));
         assert_eq!(coordinate.index(), 0);
 
-        let coordinate = Coordinate::from_tuple((4, 0));
+        let coordinate: Coordinate = Coordinate::from_tuple((4, 0));
         assert_eq!(coordinate.tuple(), (4, 0));
         assert_eq!(coordinate.index(), 4);
 
-        let coordinate = Coordinate::from_tuple((0, 1));
+        let coordinate: Coordinate = Coordinate::from_tuple((0, 1));
         assert_eq!(coordinate.tuple(), (0, 1));
         assert_eq!(coordinate.index(), 5);
 
-        let coordinate = Coordinate::from_tuple((4, 4));
+        let coordinate: Coordinate = Coordinate::from_tuple((4, 4));
         assert_eq!(coordinate.tuple(), (4, 4));
         assert_eq!(coordinate.index(), 24);
 
@@ -99,4 +100,11 @@ mod tests {
         // let result = Coordinate::from_const_tuple((5, 0));
         // assert!(result.is_err());
     }
+
+    #[test]
+    fn other_sizes() {
+        let coordinate = Coordinate::<7>::from_tuple((2, 3));
+        assert_eq!(coordinate.index(), 23);
+        assert_eq!(Coordinate::<7>::from_index(48).tuple(), (6, 6));
+    }
 }
@@ -1,69 +1,218 @@
 mod bingo_board;
 mod bingo_game;
+mod bingo_hall;
+mod const_parser;
 mod coordinate;
-mod input;
+mod monte_carlo;
+mod parser;
+mod render;
+mod rng;
+mod win_rule;
 
-const CALLS: &[u8] = input::CALLS;
-const BOARDS: &[bingo_board::BingoBoard] = input::BOARDS;
-// Exceeded interpreter step limit
-// const SOLUTION_A: usize = solve_a(CALLS, BOARDS);
+use bingo_board::BingoBoard;
+use bingo_game::Endgame;
+use win_rule::WinRule;
+
+const INPUT: &[u8] = include_bytes!("input.txt");
+const CALLS: &[u8] = &const_parser::parse_calls::<{ const_parser::count_calls(INPUT) }>(INPUT);
+const BOARDS: &[BingoBoard] =
+    &const_parser::parse_boards::<{ const_parser::count_boards(INPUT) }, 5>(INPUT);
+
+const SOLUTION_A: usize = solve_a(CALLS, BOARDS);
+const SOLUTION_B: usize = solve_b(CALLS, BOARDS);
 
 fn main() {
-    let solution_a = solve_a(CALLS, BOARDS);
-    println!("{}", solution_a);
-    let solution_b = solve_b(CALLS, BOARDS);
-    println!("{}", solution_b);
+    let mut args = std::env::args().skip(1);
+    let mut rule_arg = None;
+    let mut path = None;
+    let mut ranking = false;
+    let mut trials = None;
+    let mut seed = 0;
+    let mut watch = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rule" | "--masks" => rule_arg = Some((arg, args.next().expect("Missing value"))),
+            "--ranking" => ranking = true,
+            "--monte-carlo" => {
+                trials = Some(args.next().expect("Missing trials").parse().unwrap());
+            }
+            "--watch" => watch = Some(render::Style::detect()),
+            "--watch-plain" => watch = Some(render::Style::Plain),
+            "--seed" => seed = args.next().expect("Missing seed").parse().unwrap(),
+            _ => path = Some(arg),
+        }
+    }
+
+    let other_modes = ranking || trials.is_some() || watch.is_some();
+    if rule_arg.is_none() && path.is_none() && !other_modes {
+        println!("{}", SOLUTION_A);
+        println!("{}", SOLUTION_B);
+        return;
+    }
+
+    let input: parser::BingoInput = match path {
+        Some(path) => {
+            let file = std::fs::File::open(&path).unwrap();
+            parser::BingoInput::from_reader(std::io::BufReader::new(file)).unwrap()
+        }
+        None => parser::BingoInput {
+            calls: CALLS.to_vec(),
+            boards: BOARDS.to_vec(),
+        },
+    };
+
+    let masks;
+    let rule = match rule_arg {
+        None if !other_modes => {
+            println!("{}", solve_a(&input.calls, &input.boards));
+            println!("{}", solve_b(&input.calls, &input.boards));
+            return;
+        }
+        None => WinRule::Lines,
+        Some((flag, value)) if flag == "--rule" => WinRule::preset(&value).unwrap_or_else(|| {
+            panic!(
+                "Unknown rule {}, expected one of {:?}",
+                value,
+                WinRule::<5>::PRESETS
+            )
+        }),
+        Some((_, path)) => {
+            masks = win_rule::parse_masks(&std::fs::read_to_string(path).unwrap()).unwrap();
+            WinRule::Custom(&masks)
+        }
+    };
+
+    if let Some(style) = watch {
+        let mut hall = bingo_hall::BingoHall::new(&input.boards).with_rule(rule);
+        for (turn, call) in input.calls.iter().enumerate() {
+            hall.call(*call);
+            print!("{}", style.clear());
+            println!("Turn {}: called {}\n", turn + 1, call);
+            print!("{}", render::render_all(hall.games(), style, 5));
+            if hall.is_finished() {
+                break;
+            }
+            if style == render::Style::Ansi {
+                std::thread::sleep(std::time::Duration::from_millis(200));
+            }
+        }
+        return;
+    }
+
+    if let Some(trials) = trials {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let analysis = monte_carlo::MonteCarlo::new(&input.calls, &input.boards)
+            .with_rule(rule)
+            .with_seed(seed)
+            .with_threads(threads)
+            .run(trials);
+        print!("{}", analysis);
+        return;
+    }
+
+    if ranking {
+        let hall = bingo_hall::BingoHall::new(&input.boards).with_rule(rule);
+        for (place, placing) in hall.ranking(&input.calls).iter().enumerate() {
+            println!(
+                "{:3}. board {:3} on turn {:3}, score {}",
+                place + 1,
+                placing.board,
+                placing.turn,
+                placing.score
+            );
+        }
+        return;
+    }
+
+    println!("{}", solve_a_with(&input.calls, &input.boards, rule));
+    println!("{}", solve_b_with(&input.calls, &input.boards, rule));
+}
+
+const fn solve_a<const N: usize>(calls: &[u8], boards: &[BingoBoard<N>]) -> usize {
+    solve_a_with(calls, boards, WinRule::Lines)
+}
+
+const fn solve_b<const N: usize>(calls: &[u8], boards: &[BingoBoard<N>]) -> usize {
+    solve_b_with(calls, boards, WinRule::Lines)
 }
 
-const fn solve_a(calls: &[u8], boards: &[bingo_board::BingoBoard]) -> usize {
-    let mut endgame: Option<bingo_game::Endgame> = None;
+const fn solve_a_with<const N: usize>(
+    calls: &[u8],
+    boards: &[BingoBoard<N>],
+    rule: WinRule<'_, N>,
+) -> usize {
+    score(first_winner(calls, boards, rule))
+}
+
+const fn solve_b_with<const N: usize>(
+    calls: &[u8],
+    boards: &[BingoBoard<N>],
+    rule: WinRule<'_, N>,
+) -> usize {
+    score(last_winner(calls, boards, rule))
+}
+
+const fn first_winner<const N: usize>(
+    calls: &[u8],
+    boards: &[BingoBoard<N>],
+    rule: WinRule<'_, N>,
+) -> Option<Endgame> {
+    let mut endgame: Option<Endgame> = None;
 
     let len = boards.len();
     let mut i = 0;
     while i < len {
         let board = &boards[i];
-        let game = bingo_game::BingoGame::new(board);
-        let e = game.play(calls);
-        if let Some(ref outer_endgame) = endgame {
-            if e.turns < outer_endgame.turns {
-                endgame = Some(e);
-            }
-        } else {
-            endgame = Some(e)
-        }
+        let game = bingo_game::BingoGame::new(board).with_rule(rule);
+        endgame = earlier(endgame, game.play(calls));
 
         i += 1;
     }
 
-    if let Some(endgame) = endgame {
-        endgame.last_call as usize * endgame.unmarked_sum as usize
-    } else {
-        panic!("None found");
-    }
+    endgame
 }
 
-const fn solve_b(calls: &[u8], boards: &[bingo_board::BingoBoard]) -> usize {
-    let mut endgame: Option<bingo_game::Endgame> = None;
+const fn last_winner<const N: usize>(
+    calls: &[u8],
+    boards: &[BingoBoard<N>],
+    rule: WinRule<'_, N>,
+) -> Option<Endgame> {
+    let mut endgame: Option<Endgame> = None;
 
     let len = boards.len();
     let mut i = 0;
     while i < len {
         let board = &boards[i];
-        let game = bingo_game::BingoGame::new(board);
-        let e = game.play(calls);
-        if let Some(ref outer_endgame) = endgame {
-            if e.turns > outer_endgame.turns {
-                endgame = Some(e);
-            }
-        } else {
-            endgame = Some(e)
-        }
+        let game = bingo_game::BingoGame::new(board).with_rule(rule);
+        endgame = later(endgame, game.play(calls));
 
         i += 1;
     }
 
+    endgame
+}
+
+/// Whichever endgame finished in fewer turns, preferring `a` on a tie.
+const fn earlier(a: Option<Endgame>, b: Option<Endgame>) -> Option<Endgame> {
+    match (a, b) {
+        (Some(a), Some(b)) if b.turns < a.turns => Some(b),
+        (Some(a), _) => Some(a),
+        (None, b) => b,
+    }
+}
+
+/// Whichever endgame finished in more turns, preferring `a` on a tie.
+const fn later(a: Option<Endgame>, b: Option<Endgame>) -> Option<Endgame> {
+    match (a, b) {
+        (Some(a), Some(b)) if b.turns > a.turns => Some(b),
+        (Some(a), _) => Some(a),
+        (None, b) => b,
+    }
+}
+
+const fn score(endgame: Option<Endgame>) -> usize {
     if let Some(endgame) = endgame {
-        endgame.last_call as usize * endgame.unmarked_sum as usize
+        endgame.score()
     } else {
         panic!("None found");
     }
@@ -79,7 +228,7 @@ mod tests {
             7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22, 18, 20, 8, 19,
             3, 26, 1,
         ];
-        const BOARDS: &[bingo_board::BingoBoard] = bingo_board::parse_input! {
+        const INPUT: &[u8] = b"
             22 13 17 11  0
             8  2 23  4 24
             21  9 14 16  7
@@ -97,9 +246,43 @@ mod tests {
             18  8 23 26 20
             22 11 13  6  5
             2  0 12  3  7
-        };
+        ";
+        const BOARDS: &[BingoBoard] =
+            &const_parser::parse_boards::<{ const_parser::count_boards(INPUT) }, 5>(INPUT);
 
         const RESULT: usize = solve_a(CALLS, BOARDS);
         assert_eq!(RESULT, 4512);
     }
+
+    #[test]
+    fn other_rules() {
+        assert_eq!(solve_a_with(CALLS, BOARDS, WinRule::Lines), SOLUTION_A);
+        // Worked out by hand from the input: the last of board 87's numbers is 2, the 84th call,
+        // and every other board is still missing a number by then. Some boards need every call.
+        let first = first_winner(CALLS, BOARDS, WinRule::Blackout).unwrap();
+        assert_eq!(
+            (first.turns, first.last_call, first.unmarked_sum),
+            (84, 2, 0)
+        );
+        let last = last_winner(CALLS, BOARDS, WinRule::Blackout).unwrap();
+        assert_eq!(last.turns, CALLS.len());
+        let ranking = bingo_hall::BingoHall::new(BOARDS)
+            .with_rule(WinRule::Blackout)
+            .ranking(CALLS);
+        assert_eq!((ranking[0].board, ranking[0].turn), (87, 84));
+
+        let masks = win_rule::parse_masks("X...X\n.....\n.....\n.....\nX...X").unwrap();
+        assert_eq!(
+            solve_a_with(CALLS, BOARDS, WinRule::Custom(&masks)),
+            solve_a_with(CALLS, BOARDS, WinRule::FourCorners)
+        );
+    }
+
+    #[test]
+    fn ranking_matches_solutions() {
+        let ranking = bingo_hall::BingoHall::new(BOARDS).ranking(CALLS);
+        assert_eq!(ranking.len(), BOARDS.len());
+        assert_eq!(ranking[0].score, SOLUTION_A);
+        assert_eq!(ranking[ranking.len() - 1].score, SOLUTION_B);
+    }
 }
@@ -0,0 +1,243 @@
+//! Estimates how good each board is by playing them against many shuffled call orders.
+
+use crate::bingo_board::BingoBoard;
+use crate::bingo_game::BingoGame;
+use crate::rng::Rng;
+use crate::win_rule::WinRule;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// How many trials are played with each generator.
+const BATCH: u64 = 64;
+
+pub struct MonteCarlo<'a, const N: usize = 5> {
+    calls: &'a [u8],
+    boards: &'a [BingoBoard<N>],
+    rule: WinRule<'a, N>,
+    seed: u64,
+    threads: usize,
+}
+
+/// How one board did across every trial.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct BoardStats {
+    /// How many trials the board won, with a tie for first counting as a share of a win.
+    pub wins: f64,
+    /// The sum of the turns the board took to win, over the trials where it won at all.
+    pub total_turns: u64,
+    /// How many trials the board won at all (first or not).
+    pub finished: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    pub trials: u64,
+    pub boards: Vec<BoardStats>,
+}
+
+impl<'a, const N: usize> MonteCarlo<'a, N> {
+    /// Shuffles `calls` for each trial.
+    pub fn new(calls: &'a [u8], boards: &'a [BingoBoard<N>]) -> Self {
+        Self {
+            calls,
+            boards,
+            rule: WinRule::Lines,
+            seed: 0,
+            threads: 1,
+        }
+    }
+
+    pub fn with_rule(mut self, rule: WinRule<'a, N>) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = std::cmp::max(1, threads);
+        self
+    }
+
+    /// Plays `trials` shuffled games. Trials are played in batches of `BATCH`, each with its own
+    /// generator seeded from the seed and the batch's index, and threads take batches in turn.
+    /// Batches are combined in order, so the result depends only on the seed.
+    pub fn run(&self, trials: u64) -> Analysis {
+        let batches = trials.div_ceil(BATCH);
+        let next = AtomicU64::new(0);
+        let mut results: Vec<(u64, Vec<BoardStats>)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..self.threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = Vec::new();
+                        loop {
+                            let batch = next.fetch_add(1, Ordering::Relaxed);
+                            if batch >= batches {
+                                return results;
+                            }
+                            let trials = std::cmp::min(BATCH, trials - batch * BATCH);
+                            let rng =
+                                Rng::new(self.seed ^ batch.wrapping_mul(0xa076_1d64_78bd_642f));
+                            results.push((batch, self.run_batch(rng, trials)));
+                        }
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("Simulation thread panicked"))
+                .collect()
+        });
+        results.sort_by_key(|(batch, _)| *batch);
+
+        let mut boards = vec![BoardStats::default(); self.boards.len()];
+        for (_, result) in results {
+            for (total, stats) in boards.iter_mut().zip(result) {
+                total.wins += stats.wins;
+                total.total_turns += stats.total_turns;
+                total.finished += stats.finished;
+            }
+        }
+        Analysis { trials, boards }
+    }
+
+    fn run_batch(&self, mut rng: Rng, trials: u64) -> Vec<BoardStats> {
+        let mut stats = vec![BoardStats::default(); self.boards.len()];
+        let mut calls = self.calls.to_vec();
+        let mut turns = vec![None; self.boards.len()];
+        for _ in 0..trials {
+            rng.shuffle(&mut calls);
+            for (board, turns) in self.boards.iter().zip(turns.iter_mut()) {
+                let game = BingoGame::new(board).with_rule(self.rule);
+                *turns = game.play(&calls).map(|endgame| endgame.turns);
+            }
+
+            let first = turns.iter().flatten().min().copied();
+            let winners = turns.iter().filter(|t| t.is_some() && **t == first).count();
+            for (stats, turns) in stats.iter_mut().zip(&turns) {
+                if let Some(turns) = turns {
+                    stats.finished += 1;
+                    stats.total_turns += *turns as u64;
+                    if Some(*turns) == first {
+                        stats.wins += 1.0 / winners as f64;
+                    }
+                }
+            }
+        }
+        stats
+    }
+}
+
+impl BoardStats {
+    pub fn win_probability(&self, trials: u64) -> f64 {
+        self.wins / trials as f64
+    }
+
+    /// The average number of turns the board takes to win, if it ever did.
+    pub fn expected_turns(&self) -> Option<f64> {
+        if self.finished == 0 {
+            None
+        } else {
+            Some(self.total_turns as f64 / self.finished as f64)
+        }
+    }
+}
+
+impl Display for Analysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "board   win %   turns")?;
+        for (board, stats) in self.boards.iter().enumerate() {
+            let turns = match stats.expected_turns() {
+                Some(turns) => format!("{:7.2}", turns),
+                None => "      -".to_string(),
+            };
+            writeln!(
+                f,
+                "{:5} {:7.3} {}",
+                board,
+                stats.win_probability(self.trials) * 100.0,
+                turns
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boards() -> Vec<BingoBoard<3>> {
+        vec![
+            BingoBoard::from_arrays([[1, 2, 3], [4, 5, 6], [7, 8, 9]]),
+            BingoBoard::from_arrays([[1, 2, 3], [4, 5, 6], [7, 8, 9]]),
+            BingoBoard::from_arrays([[11, 12, 13], [14, 15, 16], [17, 18, 19]]),
+        ]
+    }
+
+    #[test]
+    fn probabilities() {
+        let calls: Vec<u8> = (1..20).collect();
+        let boards = boards();
+        let analysis = MonteCarlo::new(&calls, &boards)
+            .with_seed(1)
+            .with_threads(3)
+            .run(1000);
+
+        assert_eq!(analysis.boards.len(), 3);
+        let total: f64 = analysis.boards.iter().map(|s| s.wins).sum();
+        assert!((total - 1000.0).abs() < 1e-9);
+
+        // Identical boards always tie, and the other board is just as likely to win.
+        assert_eq!(analysis.boards[0].wins, analysis.boards[1].wins);
+        let p = analysis.boards[2].win_probability(analysis.trials);
+        assert!((0.4..0.6).contains(&p), "{}", p);
+
+        for stats in &analysis.boards {
+            assert_eq!(stats.finished, 1000);
+            let turns = stats.expected_turns().unwrap();
+            assert!((3.0..=19.0).contains(&turns), "{}", turns);
+        }
+    }
+
+    #[test]
+    fn repeatable() {
+        let calls: Vec<u8> = (1..20).collect();
+        let boards = boards();
+        let run = |seed| {
+            MonteCarlo::new(&calls, &boards)
+                .with_seed(seed)
+                .with_threads(2)
+                .run(100)
+        };
+        assert_eq!(run(5), run(5));
+        assert_ne!(run(5), run(6));
+    }
+
+    #[test]
+    fn independent_of_threads() {
+        let calls: Vec<u8> = (1..20).collect();
+        let boards = boards();
+        let run = |threads| {
+            MonteCarlo::new(&calls, &boards)
+                .with_seed(9)
+                .with_threads(threads)
+                .run(1000)
+        };
+        let single = run(1);
+        assert_eq!(single, run(3));
+        assert_eq!(single, run(8));
+    }
+
+    #[test]
+    fn never_finishing() {
+        let calls: Vec<u8> = (1..10).collect();
+        let boards = boards();
+        let analysis = MonteCarlo::new(&calls, &boards).run(10);
+        assert_eq!(analysis.boards[2], BoardStats::default());
+        assert!(analysis.to_string().lines().nth(3).unwrap().ends_with('-'));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::bingo_board::BingoBoard;
+use std::io::BufRead;
+use std::num::IntErrorKind;
+use std::str::FromStr;
+
+/// Calls and boards parsed at runtime from the puzzle's text format: a line of comma-separated
+/// calls, followed by blank-line-separated `N`x`N` boards.
+#[derive(Debug)]
+pub struct BingoInput<const N: usize = 5> {
+    pub calls: Vec<u8>,
+    pub boards: Vec<BingoBoard<N>>,
+}
+
+impl<const N: usize> BingoInput<N> {
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, BingoInputParseError> {
+        let mut lines = reader
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line));
+
+        let calls = match lines.next() {
+            Some((line_number, line)) => {
+                let line = line?;
+                if line.trim().is_empty() {
+                    return Err(BingoInputParseError::MissingCalls);
+                }
+                line.split(',')
+                    .map(|value| parse_number(line_number, value.trim()))
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+            None => return Err(BingoInputParseError::MissingCalls),
+        };
+
+        let mut boards = Vec::new();
+        let mut rows: Vec<[u8; N]> = Vec::with_capacity(N);
+        let mut board_line = 0;
+        for (line_number, line) in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                if !rows.is_empty() {
+                    return Err(BingoInputParseError::IncompleteBoard {
+                        line: board_line,
+                        expected: N,
+                        rows: rows.len(),
+                    });
+                }
+                continue;
+            }
+
+            if rows.is_empty() {
+                board_line = line_number;
+            }
+            let row = line
+                .split_whitespace()
+                .map(|value| parse_number(line_number, value))
+                .collect::<Result<Vec<_>, _>>()?;
+            let row = <[u8; N]>::try_from(row).map_err(|row| BingoInputParseError::RaggedRow {
+                line: line_number,
+                expected: N,
+                found: row.len(),
+            })?;
+            rows.push(row);
+
+            if rows.len() == N {
+                boards.push(build_board(board_line, &rows)?);
+                rows.clear();
+            }
+        }
+
+        if !rows.is_empty() {
+            return Err(BingoInputParseError::IncompleteBoard {
+                line: board_line,
+                expected: N,
+                rows: rows.len(),
+            });
+        }
+
+        Ok(Self { calls, boards })
+    }
+}
+
+impl<const N: usize> FromStr for BingoInput<N> {
+    type Err = BingoInputParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_reader(s.as_bytes())
+    }
+}
+
+fn parse_number(line: usize, value: &str) -> Result<u8, BingoInputParseError> {
+    value.parse().map_err(|err: std::num::ParseIntError| {
+        if *err.kind() == IntErrorKind::PosOverflow {
+            BingoInputParseError::ValueTooLarge {
+                line,
+                value: value.to_string(),
+            }
+        } else {
+            BingoInputParseError::InvalidNumber {
+                line,
+                value: value.to_string(),
+            }
+        }
+    })
+}
+
+fn build_board<const N: usize>(
+    line: usize,
+    rows: &[[u8; N]],
+) -> Result<BingoBoard<N>, BingoInputParseError> {
+    let mut arr = [[0; N]; N];
+    let mut seen = [false; 256];
+    for (row, values) in rows.iter().enumerate() {
+        for (column, value) in values.iter().enumerate() {
+            if seen[*value as usize] {
+                return Err(BingoInputParseError::DuplicateNumber {
+                    line,
+                    value: *value,
+                });
+            }
+            seen[*value as usize] = true;
+            arr[row][column] = *value;
+        }
+    }
+    Ok(BingoBoard::from_arrays(arr))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BingoInputParseError {
+    #[error("Could not read input: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Expected a line of calls")]
+    MissingCalls,
+
+    #[error("Line {line}: invalid number '{value}'")]
+    InvalidNumber { line: usize, value: String },
+
+    #[error("Line {line}: '{value}' is too large for a bingo number")]
+    ValueTooLarge { line: usize, value: String },
+
+    #[error("Line {line}: expected {expected} numbers in a row, found {found}")]
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("Line {line}: board has {rows} rows, expected {expected}")]
+    IncompleteBoard {
+        line: usize,
+        expected: usize,
+        rows: usize,
+    },
+
+    #[error("Line {line}: board contains {value} more than once")]
+    DuplicateNumber { line: usize, value: u8 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinate::Coordinate;
+
+    const EXAMPLE: &str = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+";
+
+    #[test]
+    fn parse_example() {
+        let input: BingoInput = EXAMPLE.parse().unwrap();
+        assert_eq!(input.calls.len(), 27);
+        assert_eq!(input.calls[..3], [7, 4, 9]);
+        assert_eq!(input.boards.len(), 3);
+        assert_eq!(input.boards[0].at(Coordinate::from_pos(0, 0)), 22);
+        assert_eq!(input.boards[0].at(Coordinate::from_pos(0, 1)), 8);
+        assert_eq!(input.boards[2].at(Coordinate::from_pos(4, 4)), 7);
+
+        assert_eq!(crate::solve_a(&input.calls, &input.boards), 4512);
+        assert_eq!(crate::solve_b(&input.calls, &input.boards), 1924);
+    }
+
+    #[test]
+    fn parse_from_reader() {
+        let input: BingoInput = BingoInput::from_reader(std::io::Cursor::new(EXAMPLE)).unwrap();
+        assert_eq!(input.boards.len(), 3);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = "1,2,3\n\n1 2 3 4\n".parse::<BingoInput>().unwrap_err();
+        assert!(matches!(
+            err,
+            BingoInputParseError::RaggedRow {
+                line: 3,
+                found: 4,
+                ..
+            }
+        ));
+
+        let err = "1,2,300\n".parse::<BingoInput>().unwrap_err();
+        assert!(matches!(
+            err,
+            BingoInputParseError::ValueTooLarge { line: 1, .. }
+        ));
+
+        let err = "1,2,x\n".parse::<BingoInput>().unwrap_err();
+        assert!(matches!(
+            err,
+            BingoInputParseError::InvalidNumber { line: 1, .. }
+        ));
+
+        let err = "1\n\n1 2 3 4 5\n6 7 8 9 10\n\n"
+            .parse::<BingoInput>()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            BingoInputParseError::IncompleteBoard {
+                line: 3,
+                rows: 2,
+                ..
+            }
+        ));
+
+        let board = "1 2 3 4 5\n6 7 8 9 10\n11 12 13 14 15\n16 17 18 19 20\n21 22 23 24 1\n";
+        let err = format!("1\n\n{}", board).parse::<BingoInput>().unwrap_err();
+        assert!(matches!(
+            err,
+            BingoInputParseError::DuplicateNumber { line: 3, value: 1 }
+        ));
+        assert_eq!(err.to_string(), "Line 3: board contains 1 more than once");
+
+        let err = "".parse::<BingoInput>().unwrap_err();
+        assert!(matches!(err, BingoInputParseError::MissingCalls));
+    }
+
+    #[test]
+    fn parse_other_sizes() {
+        let input: BingoInput<3> = "1,2,3\n\n1 2 3\n4 5 6\n7 8 9\n".parse().unwrap();
+        assert_eq!(input.boards.len(), 1);
+        assert_eq!(input.boards[0].at(Coordinate::from_pos(1, 2)), 8);
+
+        let err = "1\n\n1 2 3 4 5\n".parse::<BingoInput<3>>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Line 3: expected 3 numbers in a row, found 5"
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Draws boards with their called numbers and winning line picked out, for watching a game.
+
+use crate::bingo_game::BingoGame;
+use crate::coordinate::Coordinate;
+
+const BOLD: &str = "\x1b[1m";
+const INVERSE: &str = "\x1b[7m";
+const RESET: &str = "\x1b[0m";
+
+/// How to highlight squares.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Style {
+    /// Called numbers in bold, the winning line in inverse video.
+    Ansi,
+    /// Called numbers in brackets (`[17]`), the winning line in angle brackets (`<17>`).
+    Plain,
+}
+
+impl Style {
+    /// ANSI when stdout is a terminal and `NO_COLOR` isn't set, plain otherwise.
+    pub fn detect() -> Self {
+        use std::io::IsTerminal;
+        if std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none() {
+            Style::Ansi
+        } else {
+            Style::Plain
+        }
+    }
+
+    /// Clears the screen before redrawing, where that's possible.
+    pub fn clear(self) -> &'static str {
+        match self {
+            Style::Ansi => "\x1b[2J\x1b[H",
+            Style::Plain => "",
+        }
+    }
+
+    fn square(self, value: u8, width: usize, called: bool, winning: bool) -> String {
+        match (self, called, winning) {
+            (Style::Ansi, _, true) => format!(" {BOLD}{INVERSE}{value:width$}{RESET} "),
+            (Style::Ansi, true, false) => format!(" {BOLD}{value:width$}{RESET} "),
+            (Style::Plain, _, true) => format!("<{value:width$}>"),
+            (Style::Plain, true, false) => format!("[{value:width$}]"),
+            (_, false, false) => format!(" {value:width$} "),
+        }
+    }
+}
+
+/// How many characters the board's numbers are padded to: enough for the largest, and at least
+/// two.
+fn value_width<const N: usize>(game: &BingoGame<'_, N>) -> usize {
+    let largest = (0..N * N)
+        .map(|i| game.board().at(Coordinate::from_index(i)))
+        .max()
+        .unwrap_or(0);
+    std::cmp::max(2, largest.to_string().len())
+}
+
+/// The board's rows, each square two characters wider than `value_width`.
+pub fn render_rows<const N: usize>(game: &BingoGame<'_, N>, style: Style) -> Vec<String> {
+    let width = value_width(game);
+    let winning = game.winning_mask();
+    (0..N)
+        .map(|y| {
+            (0..N)
+                .map(|x| {
+                    let coordinate = Coordinate::from_pos(x as u8, y as u8);
+                    let called = game.is_called(coordinate);
+                    let winning = winning.is_some_and(|mask| mask.is_called(coordinate));
+                    style.square(game.board().at(coordinate), width, called, winning)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Draws boards side by side, `per_line` to a line, labelled with their index.
+pub fn render_all<const N: usize>(
+    games: &[BingoGame<'_, N>],
+    style: Style,
+    per_line: usize,
+) -> String {
+    let mut out = String::new();
+    for (chunk_index, chunk) in games.chunks(per_line.max(1)).enumerate() {
+        let labels: Vec<String> = (0..chunk.len())
+            .map(|i| {
+                let board = chunk_index * per_line.max(1) + i;
+                let won = if chunk[i].has_won() { " won" } else { "" };
+                let width = (value_width(&chunk[i]) + 2) * N;
+                format!("{:<width$}", format!("#{}{}", board, won), width = width)
+            })
+            .collect();
+        out.push_str(labels.join("  ").trim_end());
+        out.push('\n');
+
+        let rows: Vec<Vec<String>> = chunk.iter().map(|g| render_rows(g, style)).collect();
+        for y in 0..N {
+            let line: Vec<&str> = rows.iter().map(|rows| rows[y].as_str()).collect();
+            out.push_str(&line.join("  "));
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bingo_board::BingoBoard;
+
+    fn render<const N: usize>(game: &BingoGame<'_, N>, style: Style) -> String {
+        render_rows(game, style).join("\n")
+    }
+
+    #[test]
+    fn plain() {
+        let board = BingoBoard::from_arrays([[1, 2, 3], [4, 5, 6], [7, 8, 17]]);
+        let game = BingoGame::new(&board).with_called(17).with_called(4);
+        assert_eq!(
+            render(&game, Style::Plain),
+            "  1   2   3 \n[ 4]  5   6 \n  7   8 [17]"
+        );
+
+        let game = game.with_called(5).with_called(6);
+        assert_eq!(
+            render(&game, Style::Plain),
+            "  1   2   3 \n< 4>< 5>< 6>\n  7   8 [17]"
+        );
+    }
+
+    #[test]
+    fn ansi() {
+        let board = BingoBoard::from_arrays([[1, 2], [3, 4]]);
+        let game = BingoGame::new(&board).with_called(1);
+        assert_eq!(
+            render(&game, Style::Ansi),
+            " \x1b[1m 1\x1b[0m   2 \n  3   4 "
+        );
+        let game = game.with_called(2);
+        assert!(render(&game, Style::Ansi).starts_with(" \x1b[1m\x1b[7m 1\x1b[0m "));
+    }
+
+    #[test]
+    fn wide_values() {
+        let board = BingoBoard::from_arrays([[1, 100], [20, 255]]);
+        let game = BingoGame::new(&board).with_called(100);
+        assert_eq!(render(&game, Style::Plain), "   1 [100]\n  20  255 ");
+        assert_eq!(
+            render_all(&[game], Style::Plain, 1),
+            "#0\n   1 [100]\n  20  255 \n\n"
+        );
+    }
+
+    #[test]
+    fn side_by_side() {
+        let boards = [
+            BingoBoard::from_arrays([[1, 2], [3, 4]]),
+            BingoBoard::from_arrays([[5, 6], [7, 8]]),
+            BingoBoard::from_arrays([[9, 10], [11, 12]]),
+        ];
+        let games: Vec<BingoGame<2>> = boards
+            .iter()
+            .map(|b| BingoGame::new(b).with_called(1).with_called(2))
+            .collect();
+        assert_eq!(
+            render_all(&games, Style::Plain, 2),
+            "#0 won    #1\n< 1>< 2>    5   6 \n  3   4     7   8 \n\n#2\n  9  10 \n 11  12 \n\n"
+        );
+    }
+}
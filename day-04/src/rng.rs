@@ -0,0 +1,61 @@
+//! A small seeded random number generator (SplitMix64), so simulations are repeatable without
+//! pulling in a dependency.
+
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        // The bias from the modulo is negligible for the small bounds used here.
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeatable() {
+        let a: Vec<u64> = std::iter::repeat_with({
+            let mut rng = Rng::new(42);
+            move || rng.next_u64()
+        })
+        .take(10)
+        .collect();
+        let mut rng = Rng::new(42);
+        for value in a {
+            assert_eq!(rng.next_u64(), value);
+        }
+    }
+
+    #[test]
+    fn shuffle() {
+        let mut items: Vec<u8> = (0..100).collect();
+        Rng::new(7).shuffle(&mut items);
+        assert_ne!(items, (0..100).collect::<Vec<_>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..100).collect::<Vec<_>>());
+    }
+}
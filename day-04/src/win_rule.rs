@@ -0,0 +1,340 @@
+//! Patterns of chips that count as a win.
+
+use crate::bingo_game::Chips;
+use crate::coordinate::Coordinate;
+use std::str::FromStr;
+
+/// Which patterns of chips win a game.
+#[derive(Copy, Clone, Debug)]
+pub enum WinRule<'a, const N: usize = 5> {
+    /// Any complete row or column (the puzzle's rule).
+    Lines,
+    /// Either complete diagonal.
+    Diagonals,
+    /// All four corners.
+    FourCorners,
+    /// Both diagonals at once.
+    X,
+    /// A 2x2 square in any corner.
+    PostageStamp,
+    /// Every number on the board.
+    Blackout,
+    /// Any of the given masks.
+    Custom(&'a [Chips<N>]),
+}
+
+impl<'a, const N: usize> WinRule<'a, N> {
+    /// The names of the presets, as accepted by `WinRule::preset`.
+    pub const PRESETS: &'static [&'static str] = &[
+        "lines",
+        "diagonals",
+        "four-corners",
+        "x",
+        "postage-stamp",
+        "blackout",
+    ];
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "lines" => Some(WinRule::Lines),
+            "diagonals" => Some(WinRule::Diagonals),
+            "four-corners" => Some(WinRule::FourCorners),
+            "x" => Some(WinRule::X),
+            "postage-stamp" => Some(WinRule::PostageStamp),
+            "blackout" => Some(WinRule::Blackout),
+            _ => None,
+        }
+    }
+
+    pub const fn is_won(&self, chips: &Chips<N>) -> bool {
+        self.winning_mask(chips).is_some()
+    }
+
+    /// The first mask that `chips` cover, if any.
+    pub const fn winning_mask(&self, chips: &Chips<N>) -> Option<Chips<N>> {
+        match self {
+            WinRule::Lines => match first(chips, &Chips::<N>::ROWS) {
+                Some(row) => Some(row),
+                None => first(chips, &Chips::<N>::COLUMNS),
+            },
+            WinRule::Diagonals => first(chips, &Self::DIAGONALS),
+            WinRule::FourCorners => first(chips, &[Self::FOUR_CORNERS]),
+            WinRule::X => first(chips, &[Self::BOTH_DIAGONALS]),
+            WinRule::PostageStamp => first(chips, &Self::POSTAGE_STAMPS),
+            WinRule::Blackout => first(chips, &[Self::BLACKOUT]),
+            WinRule::Custom(masks) => first(chips, masks),
+        }
+    }
+
+    const DIAGONALS: [Chips<N>; 2] = {
+        let mut diagonals = [Chips::new(); 2];
+        let mut i = 0;
+        while i < N {
+            diagonals[0] = diagonals[0].called(Coordinate::from_pos(i as u8, i as u8));
+            diagonals[1] = diagonals[1].called(Coordinate::from_pos((N - 1 - i) as u8, i as u8));
+            i += 1;
+        }
+        diagonals
+    };
+
+    const BOTH_DIAGONALS: Chips<N> = Self::DIAGONALS[0].union(Self::DIAGONALS[1]);
+
+    const FOUR_CORNERS: Chips<N> = {
+        let last = (N - 1) as u8;
+        Chips::new()
+            .called(Coordinate::from_pos(0, 0))
+            .called(Coordinate::from_pos(last, 0))
+            .called(Coordinate::from_pos(0, last))
+            .called(Coordinate::from_pos(last, last))
+    };
+
+    const POSTAGE_STAMPS: [Chips<N>; 4] = {
+        let far = (N - 2) as u8;
+        let corners = [(0, 0), (far, 0), (0, far), (far, far)];
+        let mut stamps = [Chips::new(); 4];
+        let mut i = 0;
+        while i < 4 {
+            let (x, y) = corners[i];
+            stamps[i] = Chips::new()
+                .called(Coordinate::from_pos(x, y))
+                .called(Coordinate::from_pos(x + 1, y))
+                .called(Coordinate::from_pos(x, y + 1))
+                .called(Coordinate::from_pos(x + 1, y + 1));
+            i += 1;
+        }
+        stamps
+    };
+
+    const BLACKOUT: Chips<N> = {
+        let mut chips = Chips::new();
+        let mut i = 0;
+        while i < N * N {
+            chips = chips.called(Coordinate::from_index(i));
+            i += 1;
+        }
+        chips
+    };
+}
+
+const fn first<const N: usize>(chips: &Chips<N>, masks: &[Chips<N>]) -> Option<Chips<N>> {
+    let mut i = 0;
+    while i < masks.len() {
+        if chips.contains(&masks[i]) {
+            return Some(masks[i]);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Reads a mask drawn as a grid, one row per line, with `X` for a square that needs a chip and
+/// `.` for one that doesn't. Spaces between squares are ignored.
+///
+/// ```text
+/// X . . . X
+/// . . . . .
+/// . . X . .
+/// . . . . .
+/// X . . . X
+/// ```
+impl<const N: usize> FromStr for Chips<N> {
+    type Err = MaskParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chips = Chips::new();
+        let mut rows = 0;
+        for (y, line) in s
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .enumerate()
+        {
+            if y >= N {
+                return Err(MaskParseError::RowCount { expected: N });
+            }
+            let squares = line.chars().filter(|c| !c.is_whitespace());
+            let mut columns = 0;
+            for (x, c) in squares.enumerate() {
+                if x >= N {
+                    return Err(MaskParseError::RowLength {
+                        row: y + 1,
+                        expected: N,
+                    });
+                }
+                match c {
+                    'X' | 'x' => chips.set_called(Coordinate::from_pos(x as u8, y as u8)),
+                    '.' => {}
+                    c => {
+                        return Err(MaskParseError::InvalidSquare {
+                            row: y + 1,
+                            square: c,
+                        })
+                    }
+                }
+                columns += 1;
+            }
+            if columns != N {
+                return Err(MaskParseError::RowLength {
+                    row: y + 1,
+                    expected: N,
+                });
+            }
+            rows += 1;
+        }
+        if rows != N {
+            return Err(MaskParseError::RowCount { expected: N });
+        }
+        if chips == Chips::new() {
+            return Err(MaskParseError::Empty);
+        }
+        Ok(chips)
+    }
+}
+
+/// Reads any number of masks, separated by blank lines.
+pub fn parse_masks<const N: usize>(s: &str) -> Result<Vec<Chips<N>>, MaskParseError> {
+    let mut masks = Vec::new();
+    let mut block = String::new();
+    for line in s.lines().chain(std::iter::once("")) {
+        if line.trim().is_empty() {
+            if !block.is_empty() {
+                masks.push(block.parse()?);
+                block.clear();
+            }
+        } else {
+            block.push_str(line);
+            block.push('\n');
+        }
+    }
+    if masks.is_empty() {
+        return Err(MaskParseError::Empty);
+    }
+    Ok(masks)
+}
+
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum MaskParseError {
+    #[error("Mask should have {expected} rows")]
+    RowCount { expected: usize },
+
+    #[error("Row {row}: mask rows should have {expected} squares")]
+    RowLength { row: usize, expected: usize },
+
+    #[error("Row {row}: '{square}' is not a square, expected 'X' or '.'")]
+    InvalidSquare { row: usize, square: char },
+
+    #[error("Mask doesn't need any chips")]
+    Empty,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bingo_board::BingoBoard;
+    use crate::bingo_game::BingoGame;
+
+    fn board() -> BingoBoard {
+        BingoBoard::from_arrays([
+            [0, 1, 2, 3, 4],
+            [5, 6, 7, 8, 9],
+            [10, 11, 12, 13, 14],
+            [15, 16, 17, 18, 19],
+            [20, 21, 22, 23, 24],
+        ])
+    }
+
+    fn turns(rule: WinRule, calls: &[u8]) -> usize {
+        let board = board();
+        BingoGame::new(&board)
+            .with_rule(rule)
+            .play(calls)
+            .unwrap()
+            .turns
+    }
+
+    #[test]
+    fn presets() {
+        let calls = [0, 6, 4, 12, 8, 20, 18, 16, 24, 1, 5];
+        assert_eq!(turns(WinRule::Diagonals, &calls), 8);
+        assert_eq!(turns(WinRule::FourCorners, &calls), 9);
+        assert_eq!(turns(WinRule::X, &calls), 9);
+        assert_eq!(turns(WinRule::PostageStamp, &calls), 11);
+
+        let calls = [4, 8, 12, 16, 20, 0];
+        assert_eq!(turns(WinRule::Diagonals, &calls), 5);
+        assert_eq!(turns(WinRule::Lines, &(0..25).rev().collect::<Vec<_>>()), 5);
+
+        let calls: Vec<u8> = (0..25).collect();
+        assert_eq!(turns(WinRule::Blackout, &calls), 25);
+    }
+
+    #[test]
+    fn presets_by_name() {
+        for name in WinRule::<5>::PRESETS {
+            assert!(WinRule::<5>::preset(name).is_some(), "{}", name);
+        }
+        assert!(WinRule::<5>::preset("nope").is_none());
+    }
+
+    #[test]
+    fn custom_masks() {
+        let masks: Vec<Chips<5>> = parse_masks(
+            "
+            . . X . .
+            . . X . .
+            X X X X X
+            . . X . .
+            . . X . .
+
+            X X X X X
+            X . . . X
+            X . . . X
+            X . . . X
+            X X X X X
+            ",
+        )
+        .unwrap();
+        assert_eq!(masks.len(), 2);
+
+        let calls = [2, 7, 10, 11, 12, 13, 14, 17, 22];
+        assert_eq!(turns(WinRule::Custom(&masks), &calls), 9);
+
+        let x: Chips<5> = "
+            X...X
+            .X.X.
+            ..X..
+            .X.X.
+            X...X
+        "
+        .parse()
+        .unwrap();
+        assert_eq!(x, WinRule::<5>::BOTH_DIAGONALS);
+    }
+
+    #[test]
+    fn mask_errors() {
+        assert_eq!(
+            "X . .\n. . .".parse::<Chips<3>>(),
+            Err(MaskParseError::RowCount { expected: 3 })
+        );
+        assert_eq!(
+            "X . .\n. . . .\n. . .".parse::<Chips<3>>(),
+            Err(MaskParseError::RowLength {
+                row: 2,
+                expected: 3
+            })
+        );
+        assert_eq!(
+            "X . .\n. o .\n. . .".parse::<Chips<3>>(),
+            Err(MaskParseError::InvalidSquare {
+                row: 2,
+                square: 'o'
+            })
+        );
+        assert_eq!(
+            "...\n...\n...".parse::<Chips<3>>(),
+            Err(MaskParseError::Empty)
+        );
+        assert_eq!(parse_masks::<3>("\n\n"), Err(MaskParseError::Empty));
+    }
+}
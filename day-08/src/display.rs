@@ -0,0 +1,246 @@
+//! Segment displays described as data: how many segments they have, and which segments each
+//! symbol lights.
+
+use crate::solver::{self, SolveError, Wiring};
+use std::str::FromStr;
+
+/// The segments lit for each digit 0–9 on a seven-segment display, with segments `a`–`g` (top,
+/// top left, top right, middle, bottom left, bottom right, bottom) as bits 0–6.
+pub const SEVEN_SEGMENT_DIGITS: [u32; 10] = [
+    0b1110111, 0b0100100, 0b1011101, 0b1101101, 0b0101110, 0b1101011, 0b1111011, 0b0100101,
+    0b1111111, 0b1101111,
+];
+
+/// The letters of hexadecimal on a seven-segment display: `A`, `b`, `C`, `d`, `E`, `F`.
+const SEVEN_SEGMENT_HEX_LETTERS: [u32; 6] = [
+    0b0111111, 0b1111010, 0b1010011, 0b1111100, 0b1011011, 0b0011011,
+];
+
+/// `0`–`9` then `A`–`Z` on a fourteen-segment display. Bits 0–5 are the outer segments
+/// clockwise from the top, 6 and 7 the left and right halves of the middle bar, then the
+/// upper-left diagonal, upper centre, upper-right diagonal, lower-left diagonal, lower centre
+/// and lower-right diagonal.
+const FOURTEEN_SEGMENT_ALPHANUMERICS: [u32; 36] = [
+    0x0C3F, 0x0006, 0x00DB, 0x008F, 0x00E6, 0x2069, 0x00FD, 0x0007, 0x00FF, 0x00EF, // 0-9
+    0x00F7, 0x128F, 0x0039, 0x120F, 0x00F9, 0x0071, 0x00BD, 0x00F6, 0x1209, 0x001E, // A-J
+    0x2470, 0x0038, 0x0536, 0x2136, 0x003F, 0x00F3, 0x203F, 0x20F3, 0x018D, 0x1201, // K-T
+    0x003E, 0x0C30, 0x2836, 0x2D00, 0x1500, 0x0C09, // U-Z
+];
+
+#[derive(Clone, Debug)]
+pub struct DisplayDefinition {
+    segments: usize,
+    symbols: Vec<(char, u32)>,
+}
+
+impl DisplayDefinition {
+    /// Checks that every symbol can be told apart from a scrambled display: the symbols must
+    /// light different segments, and seeing every symbol once must pin down the wiring.
+    pub fn new(segments: usize, symbols: Vec<(char, u32)>) -> Result<Self, DisplayError> {
+        if segments > u32::BITS as usize {
+            return Err(DisplayError::TooManySegments(segments));
+        }
+        for (idx, (symbol, pattern)) in symbols.iter().enumerate() {
+            if segments < u32::BITS as usize && pattern >> segments != 0 {
+                return Err(DisplayError::UnknownSegment(*symbol));
+            }
+            if let Some((other, _)) = symbols[..idx].iter().find(|(_, p)| p == pattern) {
+                return Err(DisplayError::SamePattern(*other, *symbol));
+            }
+        }
+
+        let display = Self { segments, symbols };
+        match solver::solve(segments, &display.patterns(), &display.patterns()) {
+            Ok(_) => Ok(display),
+            Err(_) => Err(DisplayError::NotIdentifiable),
+        }
+    }
+
+    pub fn preset(name: &str) -> Option<Self> {
+        let (segments, symbols): (usize, Vec<(char, u32)>) = match name {
+            "digits" => (7, zip("0123456789", &SEVEN_SEGMENT_DIGITS)),
+            "hex" => {
+                let mut symbols = zip("0123456789", &SEVEN_SEGMENT_DIGITS);
+                symbols.extend(zip("AbCdEF", &SEVEN_SEGMENT_HEX_LETTERS));
+                (7, symbols)
+            }
+            "alphanumeric" => (
+                14,
+                zip(
+                    "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+                    &FOURTEEN_SEGMENT_ALPHANUMERICS,
+                ),
+            ),
+            _ => return None,
+        };
+        Some(Self::new(segments, symbols).expect("Presets are identifiable"))
+    }
+
+    fn patterns(&self) -> Vec<u32> {
+        self.symbols.iter().map(|(_, pattern)| *pattern).collect()
+    }
+
+    /// Works out the wiring from a line's patterns, then reads its output.
+    pub fn deduce(&self, line: &ScrambledLine) -> Result<String, SolveError> {
+        let patterns = self.patterns();
+        let wiring: Wiring = solver::solve(self.segments, &patterns, &line.patterns)?;
+        line.output
+            .iter()
+            .map(|wires| Ok(self.symbols[wiring.decode(*wires, &patterns)?].0))
+            .collect()
+    }
+
+    /// Parses a line of scrambled patterns, with wires named from `a` onwards.
+    pub fn parse_line(&self, line: &str) -> Result<ScrambledLine, LineParseError> {
+        let (before, after) = line
+            .split_once(" | ")
+            .ok_or(LineParseError::MissingSeparator)?;
+        let parse = |patterns: &str| {
+            patterns
+                .split_whitespace()
+                .map(|pattern| self.parse_pattern(pattern))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(ScrambledLine {
+            patterns: parse(before)?,
+            output: parse(after)?,
+        })
+    }
+
+    fn parse_pattern(&self, pattern: &str) -> Result<u32, LineParseError> {
+        pattern.chars().try_fold(0, |wires, c| {
+            let wire = (c as u32).wrapping_sub('a' as u32) as usize;
+            if wire < self.segments {
+                Ok(wires | 1 << wire)
+            } else {
+                Err(LineParseError::UnknownWire(c))
+            }
+        })
+    }
+}
+
+impl FromStr for DisplayDefinition {
+    type Err = DisplayError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::preset(s).ok_or_else(|| DisplayError::UnknownPreset(s.to_string()))
+    }
+}
+
+fn zip(symbols: &str, patterns: &[u32]) -> Vec<(char, u32)> {
+    symbols.chars().zip(patterns.iter().copied()).collect()
+}
+
+/// One line of input: scrambled patterns to learn the wiring from, and the output to read.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScrambledLine {
+    pub patterns: Vec<u32>,
+    pub output: Vec<u32>,
+}
+
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum DisplayError {
+    #[error("Unknown display '{0}'")]
+    UnknownPreset(String),
+
+    #[error("A display can have at most 32 segments, not {0}")]
+    TooManySegments(usize),
+
+    #[error("Symbol '{0}' uses a segment the display doesn't have")]
+    UnknownSegment(char),
+
+    #[error("Symbols '{0}' and '{1}' light the same segments")]
+    SamePattern(char, char),
+
+    #[error("The symbols don't pin down the wiring, even when all of them are seen")]
+    NotIdentifiable,
+}
+
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum LineParseError {
+    #[error("Missing separator")]
+    MissingSeparator,
+
+    #[error("Unknown wire '{0}'")]
+    UnknownWire(char),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Renders every symbol and then `output` on a display wired up by sending segment `n` to
+    /// wire `permutation[n]`.
+    fn scrambled_line(display: &DisplayDefinition, permutation: &[usize], output: &str) -> String {
+        let render = |pattern: u32| -> String {
+            let mut wires: Vec<char> = (0..display.segments)
+                .filter(|segment| pattern >> segment & 1 == 1)
+                .map(|segment| (b'a' + permutation[segment] as u8) as char)
+                .collect();
+            wires.sort_unstable();
+            wires.into_iter().collect()
+        };
+        let patterns: Vec<String> = display.patterns().into_iter().rev().map(render).collect();
+        let output: Vec<String> = output
+            .chars()
+            .map(|c| {
+                let (_, pattern) = display.symbols.iter().find(|(s, _)| *s == c).unwrap();
+                render(*pattern)
+            })
+            .collect();
+        format!("{} | {}", patterns.join(" "), output.join(" "))
+    }
+
+    #[test]
+    fn digits_match_puzzle() {
+        let display: DisplayDefinition = "digits".parse().unwrap();
+        let line =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+        let line = display.parse_line(line).unwrap();
+        assert_eq!(display.deduce(&line).unwrap(), "5353");
+    }
+
+    #[test]
+    fn hex() {
+        let display: DisplayDefinition = "hex".parse().unwrap();
+        let line = scrambled_line(&display, &[6, 2, 4, 0, 1, 5, 3], "C0FFEE");
+        let line = display.parse_line(&line).unwrap();
+        assert_eq!(display.deduce(&line).unwrap(), "C0FFEE");
+    }
+
+    #[test]
+    fn alphanumeric() {
+        let display: DisplayDefinition = "alphanumeric".parse().unwrap();
+        let permutation = [9, 3, 12, 0, 7, 13, 1, 10, 4, 11, 2, 6, 8, 5];
+        let line = scrambled_line(&display, &permutation, "HELLO2021");
+        let line = display.parse_line(&line).unwrap();
+        assert_eq!(display.deduce(&line).unwrap(), "HELLO2021");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            DisplayDefinition::new(2, vec![('x', 0b01), ('y', 0b10)]).unwrap_err(),
+            DisplayError::NotIdentifiable
+        );
+        assert_eq!(
+            DisplayDefinition::new(2, vec![('x', 0b01), ('y', 0b01)]).unwrap_err(),
+            DisplayError::SamePattern('x', 'y')
+        );
+        assert_eq!(
+            DisplayDefinition::new(2, vec![('x', 0b100)]).unwrap_err(),
+            DisplayError::UnknownSegment('x')
+        );
+        assert!(DisplayDefinition::new(2, vec![('x', 0b01), ('y', 0b11)]).is_ok());
+        assert!("nixie".parse::<DisplayDefinition>().is_err());
+
+        let display: DisplayDefinition = "digits".parse().unwrap();
+        assert_eq!(
+            display.parse_line("ab | h"),
+            Err(LineParseError::UnknownWire('h'))
+        );
+        // Just the 8 isn't enough to read anything.
+        let line = display.parse_line("abcdefg | abcdefg").unwrap();
+        assert_eq!(display.deduce(&line), Err(SolveError::Ambiguous));
+    }
+}
@@ -0,0 +1,142 @@
+//! Generates scrambled display lines in the puzzle's format, along with the answers they should
+//! decode to.
+
+use crate::display::SEVEN_SEGMENT_DIGITS;
+use crate::solver::Wiring;
+
+/// A small seeded random number generator (SplitMix64), so generated inputs are repeatable.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+/// A generated line and what it should decode to.
+#[derive(Clone, Debug)]
+pub struct Generated {
+    pub line: String,
+    /// `wire_of_segment[n]` is the wire (`0` for `a`) that drives segment `n`.
+    pub wire_of_segment: [usize; 7],
+    pub output: usize,
+}
+
+impl Generated {
+    /// The ground truth as the solver would report it.
+    pub fn wiring(&self) -> Wiring {
+        let mut segment_of_wire = vec![0; 7];
+        for (segment, wire) in self.wire_of_segment.iter().enumerate() {
+            segment_of_wire[*wire] = segment;
+        }
+        Wiring::new(segment_of_wire)
+    }
+}
+
+pub fn generate(rng: &mut Rng) -> Generated {
+    let mut wire_of_segment = [0, 1, 2, 3, 4, 5, 6];
+    rng.shuffle(&mut wire_of_segment);
+
+    let mut order: Vec<usize> = (0..10).collect();
+    rng.shuffle(&mut order);
+    let unique_patterns: Vec<String> = order
+        .into_iter()
+        .map(|digit| render(rng, &wire_of_segment, digit))
+        .collect();
+
+    let output_digits: Vec<usize> = (0..4).map(|_| rng.below(10)).collect();
+    let output = output_digits
+        .iter()
+        .fold(0, |value, digit| value * 10 + digit);
+    let output_patterns: Vec<String> = output_digits
+        .into_iter()
+        .map(|digit| render(rng, &wire_of_segment, digit))
+        .collect();
+
+    Generated {
+        line: format!(
+            "{} | {}",
+            unique_patterns.join(" "),
+            output_patterns.join(" ")
+        ),
+        wire_of_segment,
+        output,
+    }
+}
+
+/// The wires lit for a digit. They're listed in a random order, as in the puzzle.
+fn render(rng: &mut Rng, wire_of_segment: &[usize; 7], digit: usize) -> String {
+    let mut wires: Vec<char> = (0..7)
+        .filter(|segment| SEVEN_SEGMENT_DIGITS[digit] >> segment & 1 == 1)
+        .map(|segment| (b'a' + wire_of_segment[segment] as u8) as char)
+        .collect();
+    rng.shuffle(&mut wires);
+    wires.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{solver, Input, SignalPattern, SignalWire};
+
+    #[test]
+    fn deduces_ground_truth() {
+        let mut rng = Rng::new(0x5eed);
+        for _ in 0..2000 {
+            let generated = generate(&mut rng);
+            let input: Input = generated.line.parse().unwrap();
+            assert_eq!(
+                input.deduce_output(),
+                generated.output,
+                "{}",
+                generated.line
+            );
+            assert_eq!(input.solve_output().unwrap(), generated.output);
+
+            let segment_map = input.determine_segments();
+            let wire = |segment: usize| generated.wire_of_segment[segment] as u8 + b'a';
+            let wire =
+                |segment| -> SignalWire { (wire(segment) as char).to_string().parse().unwrap() };
+            assert_eq!(segment_map.top, wire(0));
+            assert_eq!(segment_map.middle, wire(3));
+            assert_eq!(segment_map.bottom, wire(6));
+
+            let patterns: Vec<u32> = input
+                .unique_patterns
+                .iter()
+                .map(SignalPattern::wires)
+                .collect();
+            let wiring = solver::solve(7, &SEVEN_SEGMENT_DIGITS, &patterns).unwrap();
+            assert_eq!(wiring, generated.wiring());
+        }
+    }
+
+    #[test]
+    fn repeatable() {
+        let line = |seed| generate(&mut Rng::new(seed)).line;
+        assert_eq!(line(1), line(1));
+        assert_ne!(line(1), line(2));
+    }
+}
@@ -1,14 +1,84 @@
+mod display;
+mod generator;
+mod solver;
+
 use std::{collections::HashSet, str::FromStr};
 
 type Result<T, E = Box<dyn std::error::Error>> = std::result::Result<T, E>;
 
 fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let first = args.next();
+    if let Some("--bench") = first.as_deref() {
+        let lines = match args.next() {
+            Some(lines) => lines.parse()?,
+            None => 1_000_000,
+        };
+        bench(lines)?;
+        return Ok(());
+    }
+    if let Some("--display") = first.as_deref() {
+        let display: display::DisplayDefinition = args.next().ok_or("Missing display")?.parse()?;
+        let path = args.next().ok_or("Missing input path")?;
+        for line in std::fs::read_to_string(path)?.lines() {
+            let line = display.parse_line(line)?;
+            println!("{}", display.deduce(&line)?);
+        }
+        return Ok(());
+    }
+
     let input = include_str!("input.txt");
     let input = parse(input)?;
     let count = solve_a(&input);
     println!("{}", count);
+    if std::env::args().any(|arg| arg == "--solver") {
+        let sum = solve_b_with_solver(&input)?;
+        println!("{}", sum);
+    } else {
+        let sum = solve_b(&input);
+        println!("{}", sum);
+    }
+
+    Ok(())
+}
+
+/// Times both ways of deducing the output over generated lines.
+fn bench(lines: usize) -> Result<()> {
+    let mut rng = generator::Rng::new(0x5eed);
+    let start = std::time::Instant::now();
+    let generated: Vec<generator::Generated> =
+        (0..lines).map(|_| generator::generate(&mut rng)).collect();
+    let input = generated
+        .iter()
+        .map(|g| g.line.parse::<Input>())
+        .collect::<Result<Vec<_>, _>>()?;
+    println!(
+        "generated and parsed {} lines in {:?}",
+        lines,
+        start.elapsed()
+    );
+    let expected: usize = generated.iter().map(|g| g.output).sum();
+
+    let start = std::time::Instant::now();
     let sum = solve_b(&input);
-    println!("{}", sum);
+    println!("deduce_output: {:?}", start.elapsed());
+    assert_eq!(sum, expected);
+
+    let start = std::time::Instant::now();
+    let sum = solve_b_with_solver(&input)?;
+    println!("solver: {:?}", start.elapsed());
+    assert_eq!(sum, expected);
+
+    for (generated, item) in generated.iter().zip(&input) {
+        let patterns: Vec<u32> = item
+            .unique_patterns
+            .iter()
+            .map(SignalPattern::wires)
+            .collect();
+        let wiring = solver::solve(7, &display::SEVEN_SEGMENT_DIGITS, &patterns)?;
+        assert_eq!(wiring, generated.wiring(), "{}", generated.line);
+    }
+    println!("every wiring matches the generator's");
 
     Ok(())
 }
@@ -40,6 +110,14 @@ fn solve_b(input: &[Input]) -> usize {
     sum
 }
 
+fn solve_b_with_solver(input: &[Input]) -> Result<usize, solver::SolveError> {
+    let mut sum = 0;
+    for item in input {
+        sum += item.solve_output()?;
+    }
+    Ok(sum)
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 enum SignalWire {
     A,
@@ -92,6 +170,13 @@ impl SignalPattern {
         self.signals.len()
     }
 
+    /// The pattern as a bitmask, with wire `a` as bit 0.
+    fn wires(&self) -> u32 {
+        self.signals
+            .iter()
+            .fold(0, |wires, wire| wires | 1 << *wire as u32)
+    }
+
     fn is_one(&self) -> bool {
         self.signals_set() == 2
     }
@@ -172,7 +257,7 @@ impl Input {
         self.unique_patterns
             .as_slice()
             .iter()
-            .filter(|v| f(*v))
+            .filter(|v| f(v))
             .collect()
     }
 
@@ -357,9 +442,22 @@ impl Input {
         val += signal_map.to_number(&self.output[0]).unwrap() * 1000;
         val += signal_map.to_number(&self.output[1]).unwrap() * 100;
         val += signal_map.to_number(&self.output[2]).unwrap() * 10;
-        val += signal_map.to_number(&self.output[3]).unwrap() * 1;
+        val += signal_map.to_number(&self.output[3]).unwrap();
         val
     }
+
+    /// Like `deduce_output`, but using the general constraint solver.
+    fn solve_output(&self) -> Result<usize, solver::SolveError> {
+        let patterns = self.unique_patterns.iter().map(SignalPattern::wires);
+        let symbols = &display::SEVEN_SEGMENT_DIGITS;
+        let wiring = solver::solve(7, symbols, &patterns.collect::<Vec<_>>())?;
+
+        let mut val = 0;
+        for pattern in &self.output {
+            val = val * 10 + wiring.decode(pattern.wires(), symbols)?;
+        }
+        Ok(val)
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -573,8 +671,7 @@ impl SegmentMap {
             ]),
         ];
 
-        'outer: for i in 0..=9 {
-            let positive = &numbers[i];
+        'outer: for (i, positive) in numbers.iter().enumerate() {
             let negative = all.difference(positive);
             for v in positive {
                 if !pattern.contains(*v) {
@@ -667,4 +764,14 @@ mod tests {
         assert_eq!(sum, 61229);
         Ok(())
     }
+
+    #[test]
+    fn solver_matches_deduce_output() -> Result<()> {
+        for input in [include_str!("example.txt"), include_str!("input.txt")] {
+            for item in parse(input)? {
+                assert_eq!(item.solve_output()?, item.deduce_output());
+            }
+        }
+        Ok(())
+    }
 }
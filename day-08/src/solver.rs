@@ -0,0 +1,255 @@
+//! Works out which wire drives which segment by treating it as a constraint problem.
+//!
+//! Each wire starts out able to drive any segment. The observed patterns narrow that down: a
+//! pattern must light up one of the symbols with the same number of segments, so its wires can
+//! only drive segments used by one of those symbols, and the other wires can't drive a segment
+//! that all of those symbols use. Combined with every wire driving a different segment, this
+//! usually settles the wiring on its own; when it doesn't, a wire is guessed and the search
+//! backtracks on a contradiction.
+//!
+//! Patterns, symbols and domains are all bitmasks, with bit `n` standing for wire or segment `n`.
+
+/// Which segment each wire drives.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Wiring {
+    segment_of_wire: Vec<usize>,
+}
+
+impl Wiring {
+    pub fn new(segment_of_wire: Vec<usize>) -> Self {
+        Self { segment_of_wire }
+    }
+
+    /// The segments lit by a pattern of wires.
+    pub fn map(&self, wires: u32) -> u32 {
+        self.segment_of_wire
+            .iter()
+            .enumerate()
+            .filter(|(wire, _)| wires >> wire & 1 == 1)
+            .fold(0, |segments, (_, segment)| segments | 1 << segment)
+    }
+
+    /// The index in `symbols` of the symbol a pattern of wires shows.
+    pub fn decode(&self, wires: u32, symbols: &[u32]) -> Result<usize, SolveError> {
+        let segments = self.map(wires);
+        symbols
+            .iter()
+            .position(|symbol| *symbol == segments)
+            .ok_or(SolveError::UnknownPattern(wires))
+    }
+}
+
+/// Finds the only wiring under which every pattern shows a different one of `symbols`.
+pub fn solve(segments: usize, symbols: &[u32], patterns: &[u32]) -> Result<Wiring, SolveError> {
+    let all = if segments == 32 {
+        u32::MAX
+    } else {
+        (1 << segments) - 1
+    };
+    let domains = vec![all; segments];
+    let mut solutions = Vec::new();
+    search(domains, symbols, patterns, &mut solutions);
+
+    match solutions.len() {
+        0 => Err(SolveError::Unsatisfiable),
+        1 => Ok(Wiring::new(solutions.pop().expect("One solution"))),
+        _ => Err(SolveError::Ambiguous),
+    }
+}
+
+/// Collects solutions into `solutions`, stopping once there are two (which is enough to know
+/// the answer is ambiguous).
+fn search(
+    mut domains: Vec<u32>,
+    symbols: &[u32],
+    patterns: &[u32],
+    solutions: &mut Vec<Vec<usize>>,
+) {
+    if !propagate(&mut domains, symbols, patterns) {
+        return;
+    }
+
+    let undecided = domains
+        .iter()
+        .enumerate()
+        .filter(|(_, domain)| domain.count_ones() > 1)
+        .min_by_key(|(_, domain)| domain.count_ones());
+    let (wire, domain) = match undecided {
+        Some((wire, domain)) => (wire, *domain),
+        None => {
+            let wiring: Vec<usize> = domains
+                .iter()
+                .map(|d| d.trailing_zeros() as usize)
+                .collect();
+            if is_solution(&wiring, symbols, patterns) {
+                solutions.push(wiring);
+            }
+            return;
+        }
+    };
+
+    for segment in bits(domain) {
+        if solutions.len() > 1 {
+            return;
+        }
+        let mut guess = domains.clone();
+        guess[wire] = 1 << segment;
+        search(guess, symbols, patterns, solutions);
+    }
+}
+
+/// Narrows the domains until nothing changes. Returns `false` on a contradiction.
+fn propagate(domains: &mut [u32], symbols: &[u32], patterns: &[u32]) -> bool {
+    loop {
+        let before = domains.to_vec();
+
+        for pattern in patterns {
+            // The symbols this pattern could still be showing.
+            let mut union = 0;
+            let mut intersection = u32::MAX;
+            let mut any = false;
+            for symbol in symbols {
+                if symbol.count_ones() == pattern.count_ones()
+                    && could_show(domains, *pattern, *symbol)
+                {
+                    union |= symbol;
+                    intersection &= symbol;
+                    any = true;
+                }
+            }
+            if !any {
+                return false;
+            }
+            for (wire, domain) in domains.iter_mut().enumerate() {
+                if pattern >> wire & 1 == 1 {
+                    *domain &= union;
+                } else {
+                    *domain &= !intersection;
+                }
+            }
+        }
+
+        // Every wire drives a different segment.
+        for wire in 0..domains.len() {
+            if domains[wire].count_ones() == 1 {
+                let segment = domains[wire];
+                for (other, domain) in domains.iter_mut().enumerate() {
+                    if other != wire {
+                        *domain &= !segment;
+                    }
+                }
+            }
+        }
+        for segment in 0..domains.len() {
+            let mut wires = (0..domains.len()).filter(|wire| domains[*wire] >> segment & 1 == 1);
+            match (wires.next(), wires.next()) {
+                (None, _) => return false,
+                (Some(wire), None) => domains[wire] = 1 << segment,
+                _ => {}
+            }
+        }
+
+        if domains.contains(&0) {
+            return false;
+        }
+        if domains == before {
+            return true;
+        }
+    }
+}
+
+/// Whether the pattern's wires could still light exactly `symbol`'s segments.
+fn could_show(domains: &[u32], pattern: u32, symbol: u32) -> bool {
+    let mut reachable = 0;
+    for wire in bits(pattern) {
+        if domains[wire] & symbol == 0 {
+            return false;
+        }
+        reachable |= domains[wire] & symbol;
+    }
+    reachable == symbol
+}
+
+fn is_solution(wiring: &[usize], symbols: &[u32], patterns: &[u32]) -> bool {
+    let wiring = Wiring::new(wiring.to_vec());
+    let mut shown = Vec::with_capacity(patterns.len());
+    for pattern in patterns {
+        match wiring.decode(*pattern, symbols) {
+            Ok(symbol) if !shown.contains(&symbol) => shown.push(symbol),
+            _ => return false,
+        }
+    }
+    true
+}
+
+fn bits(mask: u32) -> impl Iterator<Item = usize> {
+    (0..32).filter(move |bit| mask >> bit & 1 == 1)
+}
+
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum SolveError {
+    #[error("No wiring shows every pattern as a different symbol")]
+    Unsatisfiable,
+
+    #[error("More than one wiring fits the patterns")]
+    Ambiguous,
+
+    #[error("Pattern {0:#b} doesn't show any symbol")]
+    UnknownPattern(u32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::SEVEN_SEGMENT_DIGITS;
+
+    /// Scrambles `symbols` by sending segment `n` to wire `permutation[n]`.
+    fn scramble(symbols: &[u32], permutation: &[usize]) -> Vec<u32> {
+        symbols
+            .iter()
+            .map(|symbol| bits(*symbol).fold(0, |wires, segment| wires | 1 << permutation[segment]))
+            .collect()
+    }
+
+    #[test]
+    fn identity() {
+        let wiring = solve(7, &SEVEN_SEGMENT_DIGITS, &SEVEN_SEGMENT_DIGITS).unwrap();
+        assert_eq!(wiring, Wiring::new((0..7).collect()));
+    }
+
+    #[test]
+    fn scrambled() {
+        let permutation = [3, 5, 0, 6, 1, 4, 2];
+        let patterns = scramble(&SEVEN_SEGMENT_DIGITS, &permutation);
+        let wiring = solve(7, &SEVEN_SEGMENT_DIGITS, &patterns).unwrap();
+        for (segment, wire) in permutation.iter().enumerate() {
+            assert_eq!(wiring.segment_of_wire[*wire], segment);
+        }
+        for (digit, pattern) in patterns.iter().enumerate() {
+            assert_eq!(wiring.decode(*pattern, &SEVEN_SEGMENT_DIGITS), Ok(digit));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        // Two copies of the same pattern can't be two different digits.
+        let mut patterns = SEVEN_SEGMENT_DIGITS.to_vec();
+        patterns[0] = patterns[6];
+        assert_eq!(
+            solve(7, &SEVEN_SEGMENT_DIGITS, &patterns),
+            Err(SolveError::Unsatisfiable)
+        );
+
+        // Only the 8 says nothing about which wire is which.
+        assert_eq!(
+            solve(7, &SEVEN_SEGMENT_DIGITS, &[0b1111111]),
+            Err(SolveError::Ambiguous)
+        );
+
+        let wiring = solve(7, &SEVEN_SEGMENT_DIGITS, &SEVEN_SEGMENT_DIGITS).unwrap();
+        assert_eq!(
+            wiring.decode(0b0000011, &SEVEN_SEGMENT_DIGITS),
+            Err(SolveError::UnknownPattern(0b0000011))
+        );
+    }
+}
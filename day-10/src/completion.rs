@@ -0,0 +1,97 @@
+//! Completing a line that ran out before closing all of its brackets.
+
+use crate::{grammar::Grammar, Bracket};
+
+/// The close brackets that finish a line, innermost first, and what they score.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Completion {
+    pub text: String,
+    /// `None` if the score is too large for a `usize`.
+    pub score: Option<usize>,
+}
+
+impl Completion {
+    pub fn new(brackets: &[Bracket], grammar: &Grammar, scoring: &impl Scoring) -> Self {
+        Self {
+            text: brackets
+                .iter()
+                .map(|bracket| grammar.close(*bracket))
+                .collect(),
+            score: scoring.score(brackets),
+        }
+    }
+}
+
+/// How a completion is scored, given its brackets in the order they're closed. Returns `None` if
+/// the score doesn't fit in a `usize`.
+pub trait Scoring {
+    fn score(&self, brackets: &[Bracket]) -> Option<usize>;
+}
+
+impl<F: Fn(&[Bracket]) -> Option<usize>> Scoring for F {
+    fn score(&self, brackets: &[Bracket]) -> Option<usize> {
+        self(brackets)
+    }
+}
+
+/// The puzzle's scoring: multiply by five and add each bracket's points. Brackets that aren't
+/// the puzzle's score nothing.
+pub struct PuzzleScoring;
+
+impl Scoring for PuzzleScoring {
+    fn score(&self, brackets: &[Bracket]) -> Option<usize> {
+        brackets.iter().try_fold(0usize, |total, bracket| {
+            total
+                .checked_mul(5)?
+                .checked_add(bracket.unconsumed_score())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Parser;
+
+    #[test]
+    fn puzzle_completions() {
+        let cases = [
+            ("[({(<(())[]>[[{[]{<()<>>", "}}]])})]", 288957),
+            ("[(()[<>])]({[<{<<[]>>(", ")}>]})", 5566),
+            ("(((({<>}<{<{<>}{[]{[]{}", "}}>}>))))", 1480781),
+            ("{<[[]]>}<{[{[{[]{()[[[]", "]]}}]}]}>", 995444),
+            ("<{([{{}}[<[[[<>{}]]]>[]]", "])}>", 294),
+        ];
+        for (line, text, score) in cases {
+            let mut parser = Parser::default();
+            assert!(parser.parse(line).is_err());
+            let completion = parser.autocomplete();
+            assert_eq!(completion.text, text);
+            assert_eq!(completion.score, Some(score));
+
+            let mut completed = Parser::default();
+            assert!(completed.parse(&format!("{}{}", line, text)).is_ok());
+        }
+    }
+
+    #[test]
+    fn other_scoring() {
+        let mut parser = Parser::default();
+        let _ = parser.parse("<{([");
+        let length = |brackets: &[crate::Bracket]| Some(brackets.len());
+        let completion = parser.autocomplete_with(&length);
+        assert_eq!(completion.text, "])}>");
+        assert_eq!(completion.score, Some(4));
+    }
+
+    #[test]
+    fn score_too_large() {
+        // Each `>` scores 4, so n of them score 5^n - 1.
+        let mut parser = Parser::default();
+        let _ = parser.parse(&"<".repeat(27));
+        assert_eq!(parser.autocomplete().score, Some(5usize.pow(27) - 1));
+
+        let mut parser = Parser::default();
+        let _ = parser.parse(&"<".repeat(28));
+        assert_eq!(parser.autocomplete().score, None);
+    }
+}
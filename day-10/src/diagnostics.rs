@@ -0,0 +1,108 @@
+//! Renders parse errors under the source they came from.
+
+use std::fmt::Write;
+
+use crate::ParseError;
+
+/// Each error, followed by its line of `source` with a `^` under the problem and a `-` under the
+/// bracket it's about. A bracket opened on an earlier line gets its own line, before the problem's.
+pub fn render(source: &str, errors: &[ParseError]) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let line = |number: usize| lines.get(number - 1).copied().unwrap_or("");
+    let mut out = String::new();
+    for error in errors {
+        let at = error.at();
+        let mut markers = vec![(at.column, '^')];
+        writeln!(out, "error: {}", error).unwrap();
+        match error.opened_at() {
+            Some(opened_at) if opened_at.line == at.line => markers.push((opened_at.column, '-')),
+            Some(opened_at) => {
+                write_marked(&mut out, line(opened_at.line), &[(opened_at.column, '-')])
+            }
+            None => {}
+        }
+        write_marked(&mut out, line(at.line), &markers);
+    }
+    out
+}
+
+/// Writes `text`, and under it each marker at its column.
+fn write_marked(out: &mut String, text: &str, markers: &[(usize, char)]) {
+    let columns = markers.iter().map(|(column, _)| *column);
+    let mut under = vec![' '; columns.fold(text.chars().count(), usize::max)];
+    for (column, marker) in markers {
+        under[column - 1] = *marker;
+    }
+    let under: String = under.into_iter().collect();
+    writeln!(out, "{}", text).unwrap();
+    writeln!(out, "{}", under.trim_end()).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grammar::Grammar, Parser};
+    use std::rc::Rc;
+
+    #[test]
+    fn carets() {
+        let source = "()\n[(])x";
+        let errors = crate::check(source);
+        assert_eq!(
+            render(source, &errors),
+            "\
+error: 2:3: Invalid bracket type. Expected Round (opened at 2:2), found Square
+[(])x
+ -^
+error: 2:4: Close bracket with no opening bracket: Round
+[(])x
+   ^
+error: 2:5: Unexpected char 'x'.
+[(])x
+    ^
+"
+        );
+
+        let errors = Parser::default().parse_recovering("<{");
+        assert_eq!(
+            render("<{", &errors),
+            "\
+error: 1:3: Unexpected EOF. Expecting close bracket of type Curly (opened at 1:2)
+<{
+ -^
+error: 1:3: Unexpected EOF. Expecting close bracket of type Angle (opened at 1:1)
+<{
+- ^
+"
+        );
+    }
+
+    #[test]
+    fn opened_on_another_line() {
+        let source = "{          (\n]\n";
+        let grammar = Grammar::default().with_ignored(" \n");
+        let errors = Parser::default()
+            .with_grammar(Rc::new(grammar))
+            .parse_recovering(source);
+        assert_eq!(
+            render(source, &errors),
+            "\
+error: 2:1: Invalid bracket type. Expected Round (opened at 1:12), found Square
+{          (
+           -
+]
+^
+error: 3:1: Unexpected EOF. Expecting close bracket of type Round (opened at 1:12)
+{          (
+           -
+
+^
+error: 3:1: Unexpected EOF. Expecting close bracket of type Curly (opened at 1:1)
+{          (
+-
+
+^
+"
+        );
+    }
+}
@@ -0,0 +1,416 @@
+//! Which delimiters a parser pairs up, and what it skips over.
+
+use crate::{Bracket, Location};
+
+/// An open and close delimiter, each of one or more characters.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Pair {
+    open: String,
+    close: String,
+    bracket: Bracket,
+}
+
+/// A stretch of text such as a string or comment whose contents aren't parsed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Region {
+    open: String,
+    pub close: String,
+    /// Inside the region, this character stops the next one from closing it.
+    escape: Option<char>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Token {
+    Open(Bracket),
+    Close(Bracket),
+    Other(char),
+    /// A region that runs off the end of the input. Holds its index.
+    Unterminated(usize),
+    Eof,
+}
+
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum GrammarError {
+    #[error("Delimiters can't be empty")]
+    Empty,
+
+    #[error("'{0}' is already a delimiter")]
+    Duplicate(String),
+
+    #[error("Unknown grammar '{0}'")]
+    UnknownPreset(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct Grammar {
+    pairs: Vec<Pair>,
+    pub regions: Vec<Region>,
+    ignored: Vec<char>,
+    /// Whether characters that aren't part of any delimiter are skipped rather than rejected.
+    ignore_others: bool,
+}
+
+impl Default for Grammar {
+    /// The puzzle's grammar: four kinds of bracket and nothing else.
+    fn default() -> Self {
+        Self::empty()
+            .with_bracket("(", ")", Bracket::Round)
+            .with_bracket("[", "]", Bracket::Square)
+            .with_bracket("{", "}", Bracket::Curly)
+            .with_bracket("<", ">", Bracket::Angle)
+    }
+}
+
+impl Grammar {
+    /// A grammar that accepts nothing but empty input.
+    pub fn empty() -> Self {
+        Self {
+            pairs: Vec::new(),
+            regions: Vec::new(),
+            ignored: Vec::new(),
+            ignore_others: false,
+        }
+    }
+
+    /// JSON's objects and arrays, skipping strings and everything else.
+    pub fn json() -> Self {
+        Self::empty()
+            .with_bracket("[", "]", Bracket::Square)
+            .with_bracket("{", "}", Bracket::Curly)
+            .with_region("\"", "\"", Some('\\'))
+            .expect("JSON grammar is valid")
+            .ignoring_others()
+    }
+
+    pub fn preset(name: &str) -> Result<Self, GrammarError> {
+        match name {
+            "brackets" => Ok(Self::default()),
+            "json" => Ok(Self::json()),
+            _ => Err(GrammarError::UnknownPreset(name.to_string())),
+        }
+    }
+
+    fn with_bracket(mut self, open: &str, close: &str, bracket: Bracket) -> Self {
+        self.pairs.push(Pair {
+            open: open.to_string(),
+            close: close.to_string(),
+            bracket,
+        });
+        self
+    }
+
+    /// Adds a pair of delimiters, which parse as `Bracket::Custom`.
+    pub fn with_pair(self, open: &str, close: &str) -> Result<Self, GrammarError> {
+        self.check_new(open)?;
+        self.check_new(close)?;
+        if open == close {
+            return Err(GrammarError::Duplicate(close.to_string()));
+        }
+        let bracket = Bracket::Custom(self.pairs.len());
+        Ok(self.with_bracket(open, close, bracket))
+    }
+
+    /// Adds a region, such as a string or comment, whose contents are skipped.
+    pub fn with_region(
+        mut self,
+        open: &str,
+        close: &str,
+        escape: Option<char>,
+    ) -> Result<Self, GrammarError> {
+        self.check_new(open)?;
+        if close.is_empty() {
+            return Err(GrammarError::Empty);
+        }
+        self.regions.push(Region {
+            open: open.to_string(),
+            close: close.to_string(),
+            escape,
+        });
+        Ok(self)
+    }
+
+    /// Skips these characters wherever they appear outside a delimiter.
+    pub fn with_ignored(mut self, chars: &str) -> Self {
+        self.ignored.extend(chars.chars());
+        self
+    }
+
+    /// Skips every character that isn't part of a delimiter.
+    pub fn ignoring_others(mut self) -> Self {
+        self.ignore_others = true;
+        self
+    }
+
+    fn check_new(&self, delimiter: &str) -> Result<(), GrammarError> {
+        if delimiter.is_empty() {
+            return Err(GrammarError::Empty);
+        }
+        let taken = self
+            .pairs
+            .iter()
+            .flat_map(|pair| [&pair.open, &pair.close])
+            .chain(self.regions.iter().map(|region| &region.open));
+        for existing in taken {
+            if existing == delimiter {
+                return Err(GrammarError::Duplicate(delimiter.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// The text that closes a bracket.
+    pub fn close(&self, bracket: Bracket) -> &str {
+        self.pairs
+            .iter()
+            .find(|pair| pair.bracket == bracket)
+            .map_or("", |pair| &pair.close)
+    }
+
+    /// Splits `input`, which starts at the beginning of line index `line`, into tokens.
+    ///
+    /// Where several delimiters start at the same place, the longest wins. The input always ends
+    /// with `Token::Eof`.
+    pub fn tokenize(&self, input: &str, line: usize) -> Vec<(Location, Token)> {
+        let chars: Vec<char> = input.chars().collect();
+        let from = Location {
+            line: line + 1,
+            column: 1,
+        };
+        self.scan(&chars, 0, from)
+            .into_iter()
+            .map(|scanned| (scanned.at, scanned.token))
+            .collect()
+    }
+
+    /// Tokenizes `chars` from index `start`, which is at `from`.
+    pub fn scan(&self, chars: &[char], start: usize, from: Location) -> Vec<Scanned> {
+        let delimiters: Vec<(Vec<char>, Token)> =
+            self.pairs
+                .iter()
+                .flat_map(|pair| {
+                    [
+                        (pair.open.chars().collect(), Token::Open(pair.bracket)),
+                        (pair.close.chars().collect(), Token::Close(pair.bracket)),
+                    ]
+                })
+                .chain(self.regions.iter().enumerate().map(|(index, region)| {
+                    (region.open.chars().collect(), Token::Unterminated(index))
+                }))
+                .collect();
+        let longest_delimiter = delimiters.iter().map(|(text, _)| text.len()).max();
+
+        let mut cursor = Cursor::at(from);
+        let mut tokens = Vec::new();
+        let mut horizon = start;
+        let mut idx = start;
+        while idx < chars.len() {
+            let at = cursor.location();
+            let longest = delimiters
+                .iter()
+                .filter(|(text, _)| chars[idx..].starts_with(text))
+                .max_by_key(|(text, _)| text.len());
+            let (len, token) = match longest {
+                Some((text, Token::Unterminated(index))) => {
+                    let start = idx + text.len();
+                    match self.regions[*index].end(&chars[start..]) {
+                        Some(len) => (text.len() + len, None),
+                        None => (chars.len() - idx, Some(Token::Unterminated(*index))),
+                    }
+                }
+                Some((text, token)) => (text.len(), Some(*token)),
+                None if self.ignore_others || self.ignored.contains(&chars[idx]) => (1, None),
+                None => (1, Some(Token::Other(chars[idx]))),
+            };
+            if let Some(token) = token {
+                tokens.push(Scanned {
+                    at,
+                    offset: idx,
+                    horizon,
+                    token,
+                });
+            }
+            // Telling which delimiter starts here meant looking as far ahead as the longest one,
+            // and a region that isn't closed depends on everything after it.
+            let examined = match token {
+                Some(Token::Unterminated(_)) => usize::MAX,
+                _ => (idx + len).max(idx + longest_delimiter.unwrap_or(1)),
+            };
+            horizon = horizon.max(examined);
+            for c in &chars[idx..idx + len] {
+                cursor.advance(*c);
+            }
+            idx += len;
+        }
+        tokens.push(Scanned {
+            at: cursor.location(),
+            offset: idx,
+            horizon,
+            token: Token::Eof,
+        });
+        tokens
+    }
+}
+
+/// A token, where it starts, and how far the input had been read to decide the tokens before it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Scanned {
+    pub at: Location,
+    /// Index of the token's first character.
+    pub offset: usize,
+    /// Characters from here on played no part in the earlier tokens, so editing them can't change
+    /// those.
+    pub horizon: usize,
+    pub token: Token,
+}
+
+impl Region {
+    /// How far into `chars`, which follow the opening delimiter, the region ends.
+    fn end(&self, chars: &[char]) -> Option<usize> {
+        let close: Vec<char> = self.close.chars().collect();
+        let mut idx = 0;
+        while idx < chars.len() {
+            if Some(chars[idx]) == self.escape {
+                idx += 2;
+            } else if chars[idx..].starts_with(&close) {
+                return Some(idx + close.len());
+            } else {
+                idx += 1;
+            }
+        }
+        None
+    }
+}
+
+/// Tracks the location of each character as the input is read.
+struct Cursor {
+    line: usize,
+    column: usize,
+}
+
+impl Cursor {
+    fn at(location: Location) -> Self {
+        Self {
+            line: location.line - 1,
+            column: location.column - 1,
+        }
+    }
+
+    fn location(&self) -> Location {
+        Location {
+            line: self.line + 1,
+            column: self.column + 1,
+        }
+    }
+
+    fn advance(&mut self, c: char) {
+        if c == '\n' {
+            self.line += 1;
+            self.column = 0;
+        } else {
+            self.column += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseError, Parser};
+    use std::rc::Rc;
+
+    fn parser(grammar: &Grammar) -> Parser {
+        Parser::default().with_grammar(Rc::new(grammar.clone()))
+    }
+
+    fn at(line: usize, column: usize) -> Location {
+        Location { line, column }
+    }
+
+    #[test]
+    fn multi_char_pairs() {
+        let grammar = Grammar::default()
+            .with_pair("<!--", "-->")
+            .unwrap()
+            .with_ignored(" ");
+        assert!(parser(&grammar).parse("<!-- (<>) [ ] -->").is_ok());
+        assert_eq!(
+            parser(&grammar).parse("<!-- ( -->"),
+            Err(ParseError::InvalidBracket {
+                expected: Bracket::Round,
+                found: Bracket::Custom(4),
+                at: at(1, 8),
+                opened_at: at(1, 6),
+            })
+        );
+        assert_eq!(
+            parser(&grammar).parse("<!-- x -->"),
+            Err(ParseError::UnexpectedChar {
+                char: 'x',
+                at: at(1, 6),
+            })
+        );
+
+        let mut incomplete = parser(&grammar);
+        assert!(incomplete.parse("<!--{ <").is_err());
+        assert_eq!(incomplete.autocomplete().text, ">}-->");
+    }
+
+    #[test]
+    fn regions() {
+        let json = Grammar::json();
+        assert!(parser(&json)
+            .parse(r#"{"a": "}\"]", "b": [1, {"c": null}]}"#)
+            .is_ok());
+        assert_eq!(
+            parser(&json).parse(r#"{"a": [}"#),
+            Err(ParseError::InvalidBracket {
+                expected: Bracket::Square,
+                found: Bracket::Curly,
+                at: at(1, 8),
+                opened_at: at(1, 7),
+            })
+        );
+        assert_eq!(
+            parser(&json).parse(r#"{"a": "\"}"#),
+            Err(ParseError::UnterminatedRegion {
+                expected: "\"".to_string(),
+                at: at(1, 7),
+            })
+        );
+    }
+
+    #[test]
+    fn locations_across_lines() {
+        let tokens = Grammar::json().tokenize("{\n  \"[\",\n  ]", 4);
+        assert_eq!(
+            tokens,
+            [
+                (at(5, 1), Token::Open(Bracket::Curly)),
+                (at(7, 3), Token::Close(Bracket::Square)),
+                (at(7, 4), Token::Eof),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let grammar = Grammar::default();
+        assert_eq!(
+            grammar.clone().with_pair("", "]").unwrap_err(),
+            GrammarError::Empty
+        );
+        assert_eq!(
+            grammar.clone().with_pair("((", "]").unwrap_err(),
+            GrammarError::Duplicate("]".to_string())
+        );
+        assert_eq!(
+            grammar.clone().with_pair("|", "|").unwrap_err(),
+            GrammarError::Duplicate("|".to_string())
+        );
+        assert_eq!(
+            grammar.with_region("(", ")", None).unwrap_err(),
+            GrammarError::Duplicate("(".to_string())
+        );
+        assert!(Grammar::preset("xml").is_err());
+    }
+}
@@ -0,0 +1,238 @@
+//! Re-parsing a line as it's edited, starting from the last point the edit can't have affected.
+
+use std::{ops::Range, rc::Rc};
+
+use crate::{completion::Completion, grammar::Grammar, Bracket, Location, ParseError, Parser};
+
+/// How many tokens apart the parser's state is saved.
+const CHECKPOINT_INTERVAL: usize = 16;
+
+/// The parser's state just before a token.
+#[derive(Clone, Debug)]
+struct Checkpoint {
+    offset: usize,
+    horizon: usize,
+    at: Location,
+    stack: Vec<(Bracket, Location)>,
+}
+
+/// A line that can be edited, and the result of parsing it so far.
+#[derive(Debug)]
+pub struct EditableLine {
+    chars: Vec<char>,
+    parser: Parser,
+    checkpoints: Vec<Checkpoint>,
+    result: Result<(), ParseError>,
+}
+
+impl EditableLine {
+    pub fn new(grammar: Rc<Grammar>, line: usize, text: &str) -> Self {
+        let mut editable = Self {
+            chars: text.chars().collect(),
+            parser: Parser::on_line(line).with_grammar(grammar),
+            checkpoints: Vec::new(),
+            result: Ok(()),
+        };
+        editable.reparse(0);
+        editable
+    }
+
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// What `Parser::parse` would return for the current text.
+    pub fn result(&self) -> &Result<(), ParseError> {
+        &self.result
+    }
+
+    pub fn autocomplete(&self) -> Completion {
+        self.parser.autocomplete()
+    }
+
+    /// Inserts `text` before the character at `idx`, which may be the end of the line.
+    pub fn insert(&mut self, idx: usize, text: &str) -> Result<(), EditError> {
+        self.check_index(idx)?;
+        self.chars.splice(idx..idx, text.chars());
+        self.reparse(idx);
+        Ok(())
+    }
+
+    /// Deletes the characters in `range`.
+    pub fn delete(&mut self, range: Range<usize>) -> Result<(), EditError> {
+        if range.start > range.end {
+            return Err(EditError::Backwards {
+                start: range.start,
+                end: range.end,
+            });
+        }
+        self.check_index(range.end)?;
+        let start = range.start;
+        self.chars.drain(range);
+        self.reparse(start);
+        Ok(())
+    }
+
+    fn check_index(&self, idx: usize) -> Result<(), EditError> {
+        if idx > self.chars.len() {
+            return Err(EditError::OutOfRange {
+                idx,
+                len: self.chars.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Parses again from the last checkpoint that's unaffected by a change at `idx`.
+    fn reparse(&mut self, idx: usize) {
+        let kept = self
+            .checkpoints
+            .partition_point(|checkpoint| checkpoint.horizon <= idx);
+        self.checkpoints.truncate(kept);
+        let (start, from, horizon) = match self.checkpoints.last() {
+            Some(checkpoint) => {
+                self.parser.stack.clone_from(&checkpoint.stack);
+                (checkpoint.offset, checkpoint.at, checkpoint.horizon)
+            }
+            None => {
+                self.parser.stack.clear();
+                let from = Location {
+                    line: self.parser.line + 1,
+                    column: 1,
+                };
+                (0, from, 0)
+            }
+        };
+
+        self.result = Ok(());
+        let tokens = self.parser.grammar.scan(&self.chars, start, from);
+        for (count, scanned) in tokens.into_iter().enumerate() {
+            if count > 0 && count % CHECKPOINT_INTERVAL == 0 {
+                self.checkpoints.push(Checkpoint {
+                    offset: scanned.offset,
+                    horizon: scanned.horizon.max(horizon),
+                    at: scanned.at,
+                    stack: self.parser.stack.clone(),
+                });
+            }
+            if let Err(error) = self.parser.consume(scanned.at, scanned.token) {
+                self.result = Err(error);
+                break;
+            }
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum EditError {
+    #[error("Index {idx} is past the end of the line, which has {len} characters")]
+    OutOfRange { idx: usize, len: usize },
+
+    #[error("Range {start}..{end} ends before it starts")]
+    Backwards { start: usize, end: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// xorshift64, to make repeatable random edits.
+    struct Random(u64);
+
+    impl Random {
+        fn below(&mut self, bound: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % bound as u64) as usize
+        }
+
+        fn text(&mut self, pieces: &[&str], len: usize) -> String {
+            (0..len).map(|_| pieces[self.below(pieces.len())]).collect()
+        }
+    }
+
+    /// Makes random edits, mostly inserting `balanced` pieces so that much of the line stays
+    /// valid, and checks the result against parsing from scratch after each one.
+    fn check_edits(grammar: Grammar, balanced: &[&str], unbalanced: &[&str], seed: u64) {
+        let grammar = Rc::new(grammar);
+        let mut random = Random(seed);
+        let text = random.text(balanced, 50);
+        let mut editable = EditableLine::new(Rc::clone(&grammar), 3, &text);
+        for _ in 0..500 {
+            let len = editable.chars.len();
+            let idx = random.below(len + 1);
+            match random.below(20) {
+                0 => editable.insert(idx, &random.text(unbalanced, 1)),
+                1 => editable.delete(idx..(idx + random.below(4)).min(len)),
+                _ => editable.insert(idx, &random.text(balanced, 1)),
+            }
+            .unwrap();
+
+            let mut parser = Parser::on_line(3).with_grammar(Rc::clone(&grammar));
+            let text = editable.text();
+            assert_eq!(*editable.result(), parser.parse(&text), "{}", text);
+            assert_eq!(editable.autocomplete(), parser.autocomplete());
+        }
+    }
+
+    #[test]
+    fn brackets() {
+        let balanced = ["()", "[]", "{}", "<>", "(())", "<[]>"];
+        let unbalanced = ["(", ")", "[", "]", "{", "}", "<", ">", "x"];
+        check_edits(Grammar::default(), &balanced, &unbalanced, 1);
+        check_edits(Grammar::default(), &balanced, &unbalanced, 2);
+    }
+
+    #[test]
+    fn multi_char_delimiters_and_regions() {
+        let grammar = Grammar::default()
+            .with_pair("<!--", "-->")
+            .unwrap()
+            .with_region("\"", "\"", Some('\\'))
+            .unwrap()
+            .with_ignored(" -!");
+        let balanced = ["()", "<>", "<!--[]-->", "\"x\"", "\"\\\"\"", "--", "!", " "];
+        let unbalanced = ["<", ">", "(", ")", "<!--", "-->", "\"", "\\"];
+        check_edits(grammar.clone(), &balanced, &unbalanced, 3);
+        check_edits(grammar, &balanced, &unbalanced, 4);
+    }
+
+    #[test]
+    fn keeps_checkpoints_before_the_edit() {
+        let text = "()".repeat(100);
+        let mut editable = EditableLine::new(Rc::new(Grammar::default()), 0, &text);
+        let checkpoints = editable.checkpoints.len();
+        assert!(checkpoints > 10);
+        editable.insert(190, ")").unwrap();
+        assert!(editable.checkpoints.len() >= checkpoints - 1);
+        assert!(editable.result().is_err());
+        editable.delete(190..191).unwrap();
+        assert_eq!(*editable.result(), Ok(()));
+    }
+
+    #[test]
+    fn out_of_range() {
+        let mut editable = EditableLine::new(Rc::new(Grammar::default()), 0, "(]");
+        assert_eq!(
+            editable.insert(3, ")"),
+            Err(EditError::OutOfRange { idx: 3, len: 2 })
+        );
+        assert_eq!(
+            editable.delete(1..5),
+            Err(EditError::OutOfRange { idx: 5, len: 2 })
+        );
+        let (start, end) = (2, 1);
+        assert_eq!(
+            editable.delete(start..end),
+            Err(EditError::Backwards { start: 2, end: 1 })
+        );
+        assert_eq!(editable.text(), "(]");
+        assert!(editable.result().is_err());
+
+        editable.insert(2, ")").unwrap();
+        editable.delete(1..2).unwrap();
+        assert_eq!(editable.text(), "()");
+        assert_eq!(*editable.result(), Ok(()));
+    }
+}
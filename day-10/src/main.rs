@@ -1,62 +1,163 @@
-fn main() {
+mod completion;
+mod diagnostics;
+mod grammar;
+mod incremental;
+
+use completion::{Completion, PuzzleScoring, Scoring};
+use grammar::{Grammar, Token};
+use std::{fmt, rc::Rc};
+
+type Result<T, E = Box<dyn std::error::Error>> = std::result::Result<T, E>;
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let first = args.next();
+    if let Some("--complete") = first.as_deref() {
+        let line = args.next().ok_or("Missing line to complete")?;
+        let mut parser = Parser::default();
+        match parser.parse(&line) {
+            Ok(()) => println!("Already complete"),
+            Err(ParseError::UnexpectedEof { .. }) => {
+                let completion = parser.autocomplete();
+                match completion.score {
+                    Some(score) => println!("{} (score {})", completion.text, score),
+                    None => println!("{} (score too large)", completion.text),
+                }
+            }
+            Err(error) => return Err(error.into()),
+        }
+        return Ok(());
+    }
+    if let Some("--edit") = first.as_deref() {
+        let text = args.next().unwrap_or_default();
+        edit(&text)?;
+        return Ok(());
+    }
+    if let Some("--grammar") = first.as_deref() {
+        // The file is parsed as a whole, so line breaks are never an error.
+        let mut grammar =
+            Grammar::preset(&args.next().ok_or("Missing grammar")?)?.with_ignored("\r\n");
+        let mut path = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--pair" => {
+                    let open = args.next().ok_or("--pair needs an open delimiter")?;
+                    let close = args.next().ok_or("--pair needs a close delimiter")?;
+                    grammar = grammar.with_pair(&open, &close)?;
+                }
+                "--region" => {
+                    let open = args.next().ok_or("--region needs an open delimiter")?;
+                    let close = args.next().ok_or("--region needs a close delimiter")?;
+                    grammar = grammar.with_region(&open, &close, None)?;
+                }
+                "--ignore" => {
+                    grammar = grammar.with_ignored(&args.next().ok_or("--ignore needs characters")?)
+                }
+                _ => path = Some(arg),
+            }
+        }
+        let path = path.ok_or("Missing input path")?;
+        let input = std::fs::read_to_string(path)?;
+        let errors = Parser::default()
+            .with_grammar(Rc::new(grammar))
+            .parse_recovering(&input);
+        print!("{}", diagnostics::render(&input, &errors));
+        println!("{} problems", errors.len());
+        return Ok(());
+    }
+    if let Some("--check") = first.as_deref() {
+        let input = match args.next() {
+            Some(path) => std::fs::read_to_string(path)?,
+            None => include_str!("input.txt").to_string(),
+        };
+        let errors = check(&input);
+        print!("{}", diagnostics::render(&input, &errors));
+        println!("{} problems", errors.len());
+        return Ok(());
+    }
+
     let input = include_str!("input.txt");
     let result = solve_a(input);
     println!("{}", result);
-    let result = solve_b(input);
+    let result = solve_b(input).ok_or("Middle score is too large")?;
     println!("{}", result);
+    Ok(())
+}
+
+/// Reads edits from stdin, one per line, and shows the result of parsing after each.
+///
+/// An edit is `insert INDEX TEXT` or `delete START END`.
+fn edit(text: &str) -> Result<()> {
+    let mut line = incremental::EditableLine::new(Rc::new(Grammar::default()), 0, text);
+    for command in std::io::stdin().lines() {
+        let command = command?;
+        let mut words = command.splitn(3, ' ');
+        let edited = match (words.next(), words.next(), words.next()) {
+            (Some("insert"), Some(idx), text) => idx
+                .parse()
+                .ok()
+                .map(|idx| line.insert(idx, text.unwrap_or(""))),
+            (Some("delete"), Some(start), Some(end)) => match (start.parse(), end.parse()) {
+                (Ok(start), Ok(end)) => Some(line.delete(start..end)),
+                _ => None,
+            },
+            _ => None,
+        };
+        match edited {
+            Some(Ok(())) => {}
+            Some(Err(error)) => {
+                eprintln!("{}", error);
+                continue;
+            }
+            None => {
+                eprintln!("Expected `insert INDEX TEXT` or `delete START END`");
+                continue;
+            }
+        }
+        println!("{}", line.text());
+        match line.result() {
+            Ok(()) => println!("ok"),
+            Err(error @ ParseError::UnexpectedEof { .. }) => {
+                println!("{} (complete with {})", error, line.autocomplete().text)
+            }
+            Err(error) => println!("{}", error),
+        }
+    }
+    Ok(())
 }
 
 fn solve_a(input: &str) -> usize {
     let mut score = 0;
     for line in input.lines() {
         let mut parser = Parser::default();
-        match parser.parse(line) {
-            Err(ParseError::InvalidBracket { found, .. }) => {
-                score += found.mismatched_score();
-            }
-            _ => {}
+        if let Err(ParseError::InvalidBracket { found, .. }) = parser.parse(line) {
+            score += found.mismatched_score();
         }
     }
     score
 }
 
-fn solve_b(input: &str) -> usize {
+/// The middle completion score, or `None` if that's too large to compute.
+fn solve_b(input: &str) -> Option<usize> {
     let mut scores = Vec::new();
     for line in input.lines() {
         let mut parser = Parser::default();
-        match parser.parse(line) {
-            Err(ParseError::UnexpectedEof { .. }) => {
-                scores.push(parser.autocomplete());
-            }
-            _ => {}
+        if let Err(ParseError::UnexpectedEof { .. }) = parser.parse(line) {
+            scores.push(parser.autocomplete().score);
         }
     }
-    scores.sort();
+    // Scores too large to compute are larger than all the others.
+    scores.sort_by_key(|score| (score.is_none(), *score));
     scores[scores.len() / 2]
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum Token {
-    Open(Bracket),
-    Close(Bracket),
-    Other(char),
-    Eof,
-}
-
-impl From<char> for Token {
-    fn from(c: char) -> Self {
-        match c {
-            '(' => Token::Open(Bracket::Round),
-            '{' => Token::Open(Bracket::Curly),
-            '[' => Token::Open(Bracket::Square),
-            '<' => Token::Open(Bracket::Angle),
-            ')' => Token::Close(Bracket::Round),
-            '}' => Token::Close(Bracket::Curly),
-            ']' => Token::Close(Bracket::Square),
-            '>' => Token::Close(Bracket::Angle),
-            c => Token::Other(c),
-        }
-    }
+/// Every problem in every line, found by parsing with recovery.
+fn check(input: &str) -> Vec<ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .flat_map(|(line, text)| Parser::on_line(line).parse_recovering(text))
+        .collect()
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -65,6 +166,8 @@ enum Bracket {
     Square,
     Curly,
     Angle,
+    /// A pair added to a grammar, numbered by its place in the grammar.
+    Custom(usize),
 }
 
 impl Bracket {
@@ -74,6 +177,7 @@ impl Bracket {
             Self::Square => 57,
             Self::Curly => 1197,
             Self::Angle => 25137,
+            Self::Custom(_) => 0,
         }
     }
 
@@ -83,72 +187,193 @@ impl Bracket {
             Self::Square => 2,
             Self::Curly => 3,
             Self::Angle => 4,
+            Self::Custom(_) => 0,
         }
     }
 }
 
+/// A position in the source, counting lines and characters from 1.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Location {
+    line: usize,
+    column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
 #[derive(Debug, thiserror::Error, Eq, PartialEq)]
 enum ParseError {
-    #[error("Invalid backet type. Expected {expected:?}, found {found:?}")]
-    InvalidBracket { expected: Bracket, found: Bracket },
+    #[error("{at}: Invalid bracket type. Expected {expected:?} (opened at {opened_at}), found {found:?}")]
+    InvalidBracket {
+        expected: Bracket,
+        found: Bracket,
+        at: Location,
+        opened_at: Location,
+    },
+
+    #[error("{at}: Unexpected EOF. Expecting close bracket of type {expected:?} (opened at {opened_at})")]
+    UnexpectedEof {
+        expected: Bracket,
+        at: Location,
+        opened_at: Location,
+    },
+
+    #[error("{at}: Close bracket with no opening bracket: {found:?}")]
+    UnexpectedCloseBracket { found: Bracket, at: Location },
 
-    #[error("Unexpected EOF. Expecting close bracket of type {expected:?}")]
-    UnexpectedEof { expected: Bracket },
+    #[error("{at}: Unexpected char '{char}'.")]
+    UnexpectedChar { char: char, at: Location },
 
-    #[error("Close bracket with no opening bracket: {found:?}")]
-    UnexpectedCloseBracket { found: Bracket },
+    #[error("{at}: Unterminated region. Expecting '{expected}'")]
+    UnterminatedRegion { expected: String, at: Location },
+}
+
+impl ParseError {
+    fn at(&self) -> Location {
+        match self {
+            Self::InvalidBracket { at, .. }
+            | Self::UnexpectedEof { at, .. }
+            | Self::UnexpectedCloseBracket { at, .. }
+            | Self::UnexpectedChar { at, .. }
+            | Self::UnterminatedRegion { at, .. } => *at,
+        }
+    }
 
-    #[error("Unexpected char '{char}'.")]
-    UnexpectedChar { char: char },
+    /// Where the bracket this error is about was opened, if it was.
+    fn opened_at(&self) -> Option<Location> {
+        match self {
+            Self::InvalidBracket { opened_at, .. } | Self::UnexpectedEof { opened_at, .. } => {
+                Some(*opened_at)
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Default)]
 struct Parser {
-    stack: Vec<Bracket>,
+    grammar: Rc<Grammar>,
+    /// Index of the line being parsed, used for locations.
+    line: usize,
+    stack: Vec<(Bracket, Location)>,
 }
 
 impl Parser {
+    fn on_line(line: usize) -> Self {
+        Self {
+            line,
+            ..Self::default()
+        }
+    }
+
+    fn with_grammar(mut self, grammar: Rc<Grammar>) -> Self {
+        self.grammar = grammar;
+        self
+    }
+
     fn parse(&mut self, input: &str) -> Result<(), ParseError> {
-        for (idx, c) in input.char_indices() {
-            let token = Token::from(c);
-            self.consume(idx, token)?;
+        for (at, token) in self.grammar.tokenize(input, self.line) {
+            self.consume(at, token)?;
         }
-        self.consume(input.len(), Token::Eof)?;
         Ok(())
     }
 
-    fn consume(&mut self, _idx: usize, token: Token) -> Result<(), ParseError> {
+    /// Parses the whole line, carrying on after each problem, and returns all of them.
+    ///
+    /// A close bracket that doesn't match the innermost open one closes the nearest one that it
+    /// does match, abandoning those in between. If none match, it's skipped as if its opening
+    /// bracket had been missing. Every bracket still open at the end is reported, innermost first.
+    fn parse_recovering(&mut self, input: &str) -> Vec<ParseError> {
+        let mut errors = Vec::new();
+        let mut end = None;
+        for (at, token) in self.grammar.tokenize(input, self.line) {
+            if token == Token::Eof {
+                end = Some(at);
+            } else if let Err(error) = self.consume(at, token) {
+                self.recover(&error);
+                errors.push(error);
+            }
+        }
+        let at = end.expect("Tokens end with Eof");
+        errors.extend(self.stack.iter().rev().map(|&(expected, opened_at)| {
+            ParseError::UnexpectedEof {
+                expected,
+                at,
+                opened_at,
+            }
+        }));
+        errors
+    }
+
+    fn recover(&mut self, error: &ParseError) {
+        if let ParseError::InvalidBracket {
+            expected,
+            found,
+            opened_at,
+            ..
+        } = *error
+        {
+            match self.stack.iter().rposition(|(open, _)| *open == found) {
+                Some(depth) => self.stack.truncate(depth),
+                None => self.stack.push((expected, opened_at)),
+            }
+        }
+    }
+
+    fn consume(&mut self, at: Location, token: Token) -> Result<(), ParseError> {
         match token {
             Token::Open(open_bracket) => {
-                self.stack.push(open_bracket);
+                self.stack.push((open_bracket, at));
                 Ok(())
             }
             Token::Close(close_bracket) => match self.stack.pop() {
                 None => Err(ParseError::UnexpectedCloseBracket {
                     found: close_bracket,
+                    at,
                 }),
-                Some(open_bracket) if open_bracket != close_bracket => {
+                Some((open_bracket, opened_at)) if open_bracket != close_bracket => {
                     Err(ParseError::InvalidBracket {
                         found: close_bracket,
                         expected: open_bracket,
+                        at,
+                        opened_at,
                     })
                 }
                 Some(_) => Ok(()),
             },
-            Token::Other(char) => Err(ParseError::UnexpectedChar { char }),
+            Token::Other(char) => Err(ParseError::UnexpectedChar { char, at }),
+            Token::Unterminated(region) => Err(ParseError::UnterminatedRegion {
+                expected: self.grammar.regions[region].close.clone(),
+                at,
+            }),
             Token::Eof => match self.stack.last() {
                 None => Ok(()),
-                Some(bracket) => Err(ParseError::UnexpectedEof { expected: *bracket }),
+                Some(&(expected, opened_at)) => Err(ParseError::UnexpectedEof {
+                    expected,
+                    at,
+                    opened_at,
+                }),
             },
         }
     }
 
-    fn autocomplete(&mut self) -> usize {
-        let mut total = 0;
-        while let Some(bracket) = self.stack.pop() {
-            total = total * 5 + bracket.unconsumed_score()
-        }
-        total
+    /// The close brackets that would finish what's been parsed so far.
+    fn autocomplete(&self) -> Completion {
+        self.autocomplete_with(&PuzzleScoring)
+    }
+
+    fn autocomplete_with(&self, scoring: &impl Scoring) -> Completion {
+        let brackets: Vec<Bracket> = self
+            .stack
+            .iter()
+            .rev()
+            .map(|(bracket, _)| *bracket)
+            .collect();
+        Completion::new(&brackets, &self.grammar, scoring)
     }
 }
 
@@ -168,11 +393,83 @@ mod tests {
             Parser::default().parse("(]"),
             Err(ParseError::InvalidBracket {
                 expected: Bracket::Round,
-                found: Bracket::Square
+                found: Bracket::Square,
+                at: Location { line: 1, column: 2 },
+                opened_at: Location { line: 1, column: 1 },
             })
         );
     }
 
+    #[test]
+    fn test_parse_recovering() {
+        let at = |column| Location { line: 1, column };
+        assert!(Parser::default()
+            .parse_recovering("[<>({}){}[([])<>]]")
+            .is_empty());
+        assert_eq!(
+            Parser::default().parse_recovering("(]"),
+            vec![
+                ParseError::InvalidBracket {
+                    expected: Bracket::Round,
+                    found: Bracket::Square,
+                    at: at(2),
+                    opened_at: at(1),
+                },
+                ParseError::UnexpectedEof {
+                    expected: Bracket::Round,
+                    at: at(3),
+                    opened_at: at(1),
+                },
+            ]
+        );
+        assert_eq!(
+            Parser::default().parse_recovering("[(])x)"),
+            vec![
+                ParseError::InvalidBracket {
+                    expected: Bracket::Round,
+                    found: Bracket::Square,
+                    at: at(3),
+                    opened_at: at(2),
+                },
+                ParseError::UnexpectedCloseBracket {
+                    found: Bracket::Round,
+                    at: at(4),
+                },
+                ParseError::UnexpectedChar {
+                    char: 'x',
+                    at: at(5)
+                },
+                ParseError::UnexpectedCloseBracket {
+                    found: Bracket::Round,
+                    at: at(6),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let input = include_str!("example.txt");
+        let errors = check(input);
+        // Recovery only adds to what parsing stops at.
+        for (line, text) in input.lines().enumerate() {
+            let first = errors.iter().find(|error| error.at().line == line + 1);
+            assert_eq!(first, Parser::on_line(line).parse(text).err().as_ref());
+        }
+        assert_eq!(
+            *errors.iter().find(|error| error.at().line == 3).unwrap(),
+            ParseError::InvalidBracket {
+                expected: Bracket::Square,
+                found: Bracket::Curly,
+                at: Location {
+                    line: 3,
+                    column: 13
+                },
+                opened_at: Location { line: 3, column: 8 },
+            }
+        );
+    }
+
     #[test]
     fn test_solve_a() {
         let input = include_str!("example.txt");
@@ -187,16 +484,30 @@ mod tests {
         assert_eq!(
             result,
             Err(ParseError::UnexpectedEof {
-                expected: Bracket::Curly
+                expected: Bracket::Curly,
+                at: Location {
+                    line: 1,
+                    column: 25
+                },
+                opened_at: Location {
+                    line: 1,
+                    column: 18
+                },
             })
         );
-        assert_eq!(parser.autocomplete(), 288957);
+        let completion = parser.autocomplete();
+        assert_eq!(completion.text, "}}]])})]");
+        assert_eq!(completion.score, Some(288957));
     }
 
     #[test]
     fn test_solve_b() {
         let input = include_str!("example.txt");
         let result = solve_b(input);
-        assert_eq!(result, 288957);
+        assert_eq!(result, Some(288957));
+
+        // The one line whose score overflows sorts last, so the middle is still known.
+        let input = format!("{}\n{}\n(", "<".repeat(30), "[".repeat(2));
+        assert_eq!(solve_b(&input), Some(5 * 2 + 2));
     }
 }
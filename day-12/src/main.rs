@@ -1,5 +1,8 @@
+mod policy;
+
+use policy::{EachCaveAtMost, Forbidding, OneSmallCaveTwice, SmallCavesOnce, VisitPolicy};
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{HashMap, VecDeque},
     fmt::Debug,
     hash::Hash,
 };
@@ -7,6 +10,31 @@ use std::{
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input = include_str!("input.txt");
     let graph = Graph::parse(input)?;
+
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("--at-most") => {
+            let visits = args.next().ok_or("--at-most needs a count")?.parse()?;
+            println!("{}", graph.count_paths(EachCaveAtMost(visits))?);
+            return Ok(());
+        }
+        Some("--forbid") => {
+            let names = args.next().ok_or("--forbid needs caves")?;
+            let names: Vec<&str> = names.split(',').collect();
+            let policy = Forbidding::new(SmallCavesOnce, &names);
+            println!("{}", graph.count_paths(policy)?);
+            let policy = Forbidding::new(OneSmallCaveTwice, &names);
+            println!("{}", graph.count_paths(policy)?);
+            return Ok(());
+        }
+        Some("--count") => {
+            println!("{}", graph.count_paths(SmallCavesOnce)?);
+            println!("{}", graph.count_paths(OneSmallCaveTwice)?);
+            return Ok(());
+        }
+        _ => {}
+    }
+
     let paths = graph.paths()?;
     let result = paths.count();
     println!("{}", result);
@@ -81,16 +109,81 @@ impl<'s> Graph<'s> {
         vec_b.push(vertex_a);
     }
 
-    pub fn paths<'g>(&'g self) -> Result<PathsIterator<'g, 's>, &'static str> {
-        PathsIterator::new(self)
+    pub fn paths<'g>(&'g self) -> Result<PathsIterator<'g, 's, SmallCavesOnce>, &'static str> {
+        self.paths_with(SmallCavesOnce)
+    }
+
+    pub fn paths_part_b<'g>(
+        &'g self,
+    ) -> Result<PathsIterator<'g, 's, OneSmallCaveTwice>, &'static str> {
+        self.paths_with(OneSmallCaveTwice)
+    }
+
+    pub fn paths_with<'g, P: VisitPolicy>(
+        &'g self,
+        policy: P,
+    ) -> Result<PathsIterator<'g, 's, P>, &'static str> {
+        PathsIterator::new(self, policy)
+    }
+
+    /// Counts the paths from start to end without listing them, by remembering how many paths
+    /// lead on from each cave in each state of the policy.
+    pub fn count_paths<P: VisitPolicy>(&self, policy: P) -> Result<u128, &'static str> {
+        if !policy.limits_big_caves() && self.has_adjacent_big_caves(&policy) {
+            return Err("big caves are adjacent, so there are infinitely many paths");
+        }
+        let start = self
+            .vertex_by_name("start")
+            .ok_or("start vertex not found")?;
+        let Some(state) = policy.visit(&policy.start(self), &start) else {
+            return Ok(0);
+        };
+        let mut memo = HashMap::new();
+        Ok(self.count_paths_from(&policy, start, state, &mut memo))
+    }
+
+    fn count_paths_from<P: VisitPolicy>(
+        &self,
+        policy: &P,
+        vertex: Vertex<'_, 's>,
+        state: P::State,
+        memo: &mut HashMap<(VertexId, P::State), u128>,
+    ) -> u128 {
+        let key = (vertex.index, state);
+        if let Some(count) = memo.get(&key) {
+            return *count;
+        }
+        let mut count = 0;
+        for next_vertex in vertex.edges() {
+            if next_vertex.is_start() {
+                continue;
+            }
+            if let Some(state) = policy.visit(&key.1, &next_vertex) {
+                count += if next_vertex.is_end() {
+                    1
+                } else {
+                    self.count_paths_from(policy, next_vertex, state, memo)
+                };
+            }
+        }
+        memo.insert(key, count);
+        count
     }
 
-    pub fn paths_part_b<'g>(&'g self) -> Result<PathsIteratorPartB<'g, 's>, &'static str> {
-        PathsIteratorPartB::new(self)
+    /// Whether a path could go back and forth between two big caves forever, as far as the
+    /// policy allows it into them.
+    fn has_adjacent_big_caves<P: VisitPolicy>(&self, policy: &P) -> bool {
+        let open = |id: &VertexId| {
+            let vertex = self.vertex_by_id(*id);
+            vertex.is_big() && policy.allows(&vertex)
+        };
+        self.edges
+            .iter()
+            .any(|(a, edges)| open(a) && edges.iter().any(open))
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 struct VertexId(usize);
 
 #[derive(Copy, Clone)]
@@ -173,140 +266,67 @@ impl<'g, 's> Iterator for EdgesIterator<'g, 's> {
     }
 }
 
-pub struct PathsIterator<'g, 's> {
+pub struct PathsIterator<'g, 's, P: VisitPolicy> {
+    policy: P,
     stack_vertices: Vec<Vertex<'g, 's>>,
     stack_iterators: VecDeque<EdgesIterator<'g, 's>>,
+    stack_states: Vec<P::State>,
 }
 
-impl<'g, 's> PathsIterator<'g, 's> {
-    pub fn new(graph: &'g Graph<'s>) -> Result<Self, &'static str> {
+impl<'g, 's, P: VisitPolicy> PathsIterator<'g, 's, P> {
+    pub fn new(graph: &'g Graph<'s>, policy: P) -> Result<Self, &'static str> {
         let start = graph
             .vertex_by_name("start")
             .ok_or("start vertex not found")?;
-        let edges = start.edges();
-        let stack_vertices = vec![start];
-        let mut stack_iterators = VecDeque::new();
-        stack_iterators.push_front(edges);
-        Ok(Self {
-            stack_iterators,
-            stack_vertices,
-        })
-    }
-
-    pub fn next_path(&mut self) -> Option<Vec<Vertex<'g, 's>>> {
-        loop {
-            let iterator = self.stack_iterators.back_mut()?;
-            if let Some(next_vertex) = iterator.next() {
-                if next_vertex.is_end() {
-                    // Found an end. Return the entire path!
-                    let mut ret = self.stack_vertices.clone();
-                    ret.push(next_vertex);
-                    return Some(ret);
-                } else if next_vertex.is_small() && self.stack_vertices.contains(&next_vertex) {
-                    // Small vertex already seen. Ignore.
-                } else {
-                    // Found the next cave. Add it to the stack and "recurse"
-                    let edges = next_vertex.edges();
-                    self.stack_vertices.push(next_vertex);
-                    self.stack_iterators.push_back(edges);
-                }
-            } else {
-                // We've looked at all of the current cave's edges. Pop it off and continue with the
-                // previous cave.
-                self.stack_vertices.pop();
-                self.stack_iterators.pop_back();
-            }
-        }
-    }
-}
-
-impl<'g, 's> Iterator for PathsIterator<'g, 's> {
-    type Item = Vec<Vertex<'g, 's>>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.next_path()
-    }
-}
-
-pub struct PathsIteratorPartB<'g, 's> {
-    stack_vertices: Vec<Vertex<'g, 's>>,
-    stack_iterators: VecDeque<EdgesIterator<'g, 's>>,
-}
-
-impl<'g, 's> PathsIteratorPartB<'g, 's> {
-    pub fn new(graph: &'g Graph<'s>) -> Result<Self, &'static str> {
-        let start = graph
-            .vertex_by_name("start")
-            .ok_or("start vertex not found")?;
-        let edges = start.edges();
-        let stack_vertices = vec![start];
-        let mut stack_iterators = VecDeque::new();
-        stack_iterators.push_front(edges);
-        Ok(Self {
-            stack_iterators,
-            stack_vertices,
-        })
-    }
-
-    fn would_be_valid_path(&self, vertex: &Vertex<'_, '_>) -> bool {
-        if vertex.is_start() {
-            // Can't revisit start twice.
-            return false;
-        }
-        if vertex.is_big() {
-            // We can always go to a big cave.
-            return true;
-        }
-
-        let mut seen_twice = false;
-        let mut seen_vertexes = HashSet::new();
-
-        for vertex in self.stack_vertices.iter().chain(std::iter::once(vertex)) {
-            if vertex.is_big() {
-                continue;
-            }
-            let already_seen = !seen_vertexes.insert(vertex);
-            if already_seen {
-                if seen_twice {
-                    // Another one has already been seen twice!
-                    return false;
-                } else {
-                    seen_twice = true;
-                }
-            }
+        let mut iterator = Self {
+            stack_iterators: VecDeque::new(),
+            stack_vertices: Vec::new(),
+            stack_states: Vec::new(),
+            policy,
+        };
+        // If the policy won't even let us start, there are no paths.
+        if let Some(state) = iterator.policy.visit(&iterator.policy.start(graph), &start) {
+            iterator.stack_iterators.push_front(start.edges());
+            iterator.stack_vertices.push(start);
+            iterator.stack_states.push(state);
         }
-
-        true
+        Ok(iterator)
     }
 
     pub fn next_path(&mut self) -> Option<Vec<Vertex<'g, 's>>> {
         loop {
             let iterator = self.stack_iterators.back_mut()?;
             if let Some(next_vertex) = iterator.next() {
-                if next_vertex.is_end() {
-                    // Found an end. Return the entire path!
-                    let mut ret = self.stack_vertices.clone();
-                    ret.push(next_vertex);
-                    return Some(ret);
-                } else if !self.would_be_valid_path(&next_vertex) {
-                    // Would not be a valid path. Ignore
-                } else {
+                let state = self.stack_states.last()?;
+                if next_vertex.is_start() {
+                    // Can't revisit start.
+                } else if let Some(state) = self.policy.visit(state, &next_vertex) {
+                    if next_vertex.is_end() {
+                        // Found an end. Return the entire path!
+                        let mut ret = self.stack_vertices.clone();
+                        ret.push(next_vertex);
+                        return Some(ret);
+                    }
                     // Found the next cave. Add it to the stack and "recurse"
                     let edges = next_vertex.edges();
                     self.stack_vertices.push(next_vertex);
                     self.stack_iterators.push_back(edges);
+                    self.stack_states.push(state);
+                } else {
+                    // The policy won't allow this cave. Ignore.
                 }
             } else {
                 // We've looked at all of the current cave's edges. Pop it off and continue with the
                 // previous cave.
                 self.stack_vertices.pop();
                 self.stack_iterators.pop_back();
+                self.stack_states.pop();
             }
         }
     }
 }
 
-impl<'g, 's> Iterator for PathsIteratorPartB<'g, 's> {
+impl<'g, 's, P: VisitPolicy> Iterator for PathsIterator<'g, 's, P> {
     type Item = Vec<Vertex<'g, 's>>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -489,6 +509,68 @@ mod tests {
         assert_eq!(count, 226);
     }
 
+    #[test]
+    fn test_count_paths() {
+        let examples = [
+            "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end",
+            "dc-end\nHN-start\nstart-kj\ndc-start\ndc-HN\nLN-dc\nHN-end\nkj-sa\nkj-HN\nkj-dc",
+            "fs-end\nhe-DX\nfs-he\nstart-DX\npj-DX\nend-zg\nzg-sl\nzg-pj\npj-he\nRW-he\nfs-DX\npj-RW\nzg-RW\nstart-pj\nhe-WI\nzg-he\npj-fs\nstart-RW",
+        ];
+        for example in examples {
+            let graph = Graph::parse(example).expect("Expected valid parse");
+            let count = graph.paths().expect("Expected paths").count() as u128;
+            assert_eq!(graph.count_paths(SmallCavesOnce), Ok(count));
+            let count = graph.paths_part_b().expect("Expected paths").count() as u128;
+            assert_eq!(graph.count_paths(OneSmallCaveTwice), Ok(count));
+            for visits in 0..3 {
+                let policy = EachCaveAtMost(visits);
+                let count = graph.paths_with(policy).expect("Expected paths").count() as u128;
+                assert_eq!(graph.count_paths(policy), Ok(count));
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_paths_on_larger_graph() {
+        // A big cave with many small caves around it. A path may call in on any of the small
+        // caves, in any order, before leaving.
+        let caves = 16;
+        let mut input = String::from("start-HUB\nHUB-end\n");
+        for cave in 0..caves {
+            input += &format!("HUB-c{}\n", cave);
+        }
+        let graph = Graph::parse(&input).expect("Expected valid parse");
+
+        // The number of ordered selections of any size.
+        let expected: u128 = (0..=caves)
+            .map(|chosen| (caves - chosen + 1..=caves).product::<u128>())
+            .sum();
+        assert_eq!(graph.count_paths(SmallCavesOnce), Ok(expected));
+    }
+
+    #[test]
+    fn test_count_paths_with_adjacent_big_caves() {
+        let graph = Graph::parse("start-A\nA-B\nB-end").expect("Expected valid parse");
+        assert!(graph.count_paths(SmallCavesOnce).is_err());
+        assert!(graph.count_paths(OneSmallCaveTwice).is_err());
+        // start,A,B,end and start,A,B,A,B,end.
+        assert_eq!(graph.count_paths(EachCaveAtMost(2)), Ok(2));
+
+        // Without A there's no going back and forth, and no way from start to end either.
+        assert_eq!(
+            graph.count_paths(Forbidding::new(SmallCavesOnce, &["A"])),
+            Ok(0)
+        );
+        let graph =
+            Graph::parse("start-A\nA-B\nB-end\nstart-c\nc-B").expect("Expected valid parse");
+        assert!(graph.count_paths(SmallCavesOnce).is_err());
+        // start,c,B,end.
+        assert_eq!(
+            graph.count_paths(Forbidding::new(SmallCavesOnce, &["A"])),
+            Ok(1)
+        );
+    }
+
     #[test]
     fn test_paths_b_on_example_1() {
         let graph = Graph::parse(
@@ -0,0 +1,206 @@
+//! Rules for which caves a path may visit, and how often.
+//!
+//! A policy follows a path one cave at a time, keeping whatever it needs to know about the caves
+//! so far in its `State`. Paths never return to `start` and stop at `end`, whatever the policy.
+
+use std::hash::Hash;
+
+use crate::{Graph, Vertex, VertexId};
+
+pub trait VisitPolicy {
+    /// What the policy remembers about a path so far.
+    type State: Clone + Eq + Hash;
+
+    /// The state of a path that hasn't visited any caves yet.
+    fn start(&self, graph: &Graph<'_>) -> Self::State;
+
+    /// The state after moving on to `vertex`, or `None` if the path may not go there.
+    fn visit(&self, state: &Self::State, vertex: &Vertex<'_, '_>) -> Option<Self::State>;
+
+    /// Whether big caves can only be visited so many times. If not, two big caves next to each
+    /// other make for infinitely many paths.
+    fn limits_big_caves(&self) -> bool {
+        false
+    }
+
+    /// Whether a path may ever visit `vertex`. Caves for which this is false are never visited,
+    /// so they can't lead to infinitely many paths.
+    fn allows(&self, _vertex: &Vertex<'_, '_>) -> bool {
+        true
+    }
+}
+
+/// A set of caves, as one bit per cave.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct VertexSet(Vec<u64>);
+
+impl VertexSet {
+    fn with_capacity(vertices: usize) -> Self {
+        Self(vec![0; vertices.div_ceil(64)])
+    }
+
+    pub(crate) fn contains(&self, id: VertexId) -> bool {
+        self.0[id.0 / 64] >> (id.0 % 64) & 1 == 1
+    }
+
+    /// Adds a cave, returning whether it wasn't already there.
+    pub(crate) fn insert(&mut self, id: VertexId) -> bool {
+        let was_absent = !self.contains(id);
+        self.0[id.0 / 64] |= 1 << (id.0 % 64);
+        was_absent
+    }
+}
+
+/// Small caves may be visited at most once. Part one's rule.
+#[derive(Copy, Clone, Debug)]
+pub struct SmallCavesOnce;
+
+impl VisitPolicy for SmallCavesOnce {
+    type State = VertexSet;
+
+    fn start(&self, graph: &Graph<'_>) -> Self::State {
+        VertexSet::with_capacity(graph.vertices.len())
+    }
+
+    fn visit(&self, state: &Self::State, vertex: &Vertex<'_, '_>) -> Option<Self::State> {
+        if vertex.is_big() {
+            return Some(state.clone());
+        }
+        let mut visited = state.clone();
+        visited.insert(vertex.index).then_some(visited)
+    }
+}
+
+/// Like `SmallCavesOnce`, except that a single small cave may be visited twice. Part two's rule.
+#[derive(Copy, Clone, Debug)]
+pub struct OneSmallCaveTwice;
+
+impl VisitPolicy for OneSmallCaveTwice {
+    /// The small caves visited, and whether one of them has been visited twice.
+    type State = (VertexSet, bool);
+
+    fn start(&self, graph: &Graph<'_>) -> Self::State {
+        (SmallCavesOnce.start(graph), false)
+    }
+
+    fn visit(&self, state: &Self::State, vertex: &Vertex<'_, '_>) -> Option<Self::State> {
+        let (visited, twice) = state;
+        match SmallCavesOnce.visit(visited, vertex) {
+            Some(visited) => Some((visited, *twice)),
+            None if !twice => Some((visited.clone(), true)),
+            None => None,
+        }
+    }
+}
+
+/// Every cave, big or small, may be visited at most this many times.
+#[derive(Copy, Clone, Debug)]
+pub struct EachCaveAtMost(pub usize);
+
+impl VisitPolicy for EachCaveAtMost {
+    /// How many times each cave has been visited.
+    type State = Vec<usize>;
+
+    fn start(&self, graph: &Graph<'_>) -> Self::State {
+        vec![0; graph.vertices.len()]
+    }
+
+    fn visit(&self, state: &Self::State, vertex: &Vertex<'_, '_>) -> Option<Self::State> {
+        if state[vertex.index.0] == self.0 {
+            return None;
+        }
+        let mut visits = state.clone();
+        visits[vertex.index.0] += 1;
+        Some(visits)
+    }
+
+    fn limits_big_caves(&self) -> bool {
+        true
+    }
+}
+
+/// Another policy, with some caves that may not be visited at all.
+#[derive(Clone, Debug)]
+pub struct Forbidding<P> {
+    policy: P,
+    names: Vec<String>,
+}
+
+impl<P: VisitPolicy> Forbidding<P> {
+    pub fn new(policy: P, names: &[&str]) -> Self {
+        Self {
+            policy,
+            names: names.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+}
+
+impl<P: VisitPolicy> VisitPolicy for Forbidding<P> {
+    type State = P::State;
+
+    fn start(&self, graph: &Graph<'_>) -> Self::State {
+        self.policy.start(graph)
+    }
+
+    fn visit(&self, state: &Self::State, vertex: &Vertex<'_, '_>) -> Option<Self::State> {
+        if !self.allows(vertex) {
+            return None;
+        }
+        self.policy.visit(state, vertex)
+    }
+
+    fn limits_big_caves(&self) -> bool {
+        self.policy.limits_big_caves()
+    }
+
+    fn allows(&self, vertex: &Vertex<'_, '_>) -> bool {
+        !self.names.iter().any(|name| name == vertex.name()) && self.policy.allows(vertex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end";
+
+    fn count(policy: impl VisitPolicy) -> usize {
+        let graph = Graph::parse(EXAMPLE).unwrap();
+        graph.paths_with(policy).unwrap().take(1000).count()
+    }
+
+    #[test]
+    fn at_most() {
+        assert_eq!(count(EachCaveAtMost(0)), 0);
+        // start,A,end and start,b,end and start,A,b,end and start,b,A,end.
+        assert_eq!(count(EachCaveAtMost(1)), 4);
+
+        let graph = Graph::parse(EXAMPLE).unwrap();
+        let at_most_twice = graph
+            .paths_with(EachCaveAtMost(3))
+            .unwrap()
+            .filter(|path| {
+                path.iter()
+                    .all(|a| path.iter().filter(|b| a == *b).count() <= 2)
+            })
+            .count();
+        assert_eq!(count(EachCaveAtMost(2)), at_most_twice);
+    }
+
+    #[test]
+    fn forbidden() {
+        assert_eq!(count(Forbidding::new(SmallCavesOnce, &["A"])), 1);
+        assert_eq!(count(Forbidding::new(SmallCavesOnce, &["c", "d"])), 5);
+        assert_eq!(count(Forbidding::new(OneSmallCaveTwice, &["end"])), 0);
+        assert_eq!(count(Forbidding::new(SmallCavesOnce, &[])), 10);
+    }
+
+    #[test]
+    fn vertex_set() {
+        let mut set = VertexSet::with_capacity(100);
+        assert!(set.insert(VertexId(70)));
+        assert!(!set.insert(VertexId(70)));
+        assert!(set.contains(VertexId(70)));
+        assert!(!set.contains(VertexId(6)));
+    }
+}
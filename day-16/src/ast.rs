@@ -1,4 +1,5 @@
-use crate::packet::{Packet, PacketParser, Payload, TypeId};
+use crate::bignum::BigUint;
+use crate::packet::{PacketParser, Payload, TypeId};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum AstNode {
@@ -7,6 +8,8 @@ pub enum AstNode {
     Minimum(Vec<AstNode>),
     Maximum(Vec<AstNode>),
     Literal(u64),
+    /// A literal too big for a `u64`, which only `evaluate_big` can use.
+    WideLiteral(BigUint),
     GreaterThan(Box<AstNode>, Box<AstNode>),
     LessThan(Box<AstNode>, Box<AstNode>),
     EqualTo(Box<AstNode>, Box<AstNode>),
@@ -19,66 +22,153 @@ impl AstNode {
         Ok(ast.evaluate())
     }
 
+    pub fn evaluate_input_big(input: &str) -> Result<BigUint, Error> {
+        let mut builder = AstBuilder::new(input);
+        let ast = builder.build()?;
+        Ok(ast.evaluate_big())
+    }
+
+    /// Evaluates the tree, panicking if a literal or the result overflows a `u64`.
+    ///
+    /// Use `evaluate_checked` to handle overflow, or `evaluate_big` for an exact answer.
     pub fn evaluate(self) -> u64 {
+        match self.evaluate_checked() {
+            Ok(value) => value,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Like `evaluate`, but reports overflow (and where it happened) as an error.
+    pub fn evaluate_checked(&self) -> Result<u64, EvaluateError> {
+        let mut path = Vec::new();
+        self.evaluate_checked_at(&mut path)
+    }
+
+    fn evaluate_checked_at(&self, path: &mut Vec<usize>) -> Result<u64, EvaluateError> {
+        let overflow = |path: &Vec<usize>| EvaluateError::Overflow {
+            path: NodePath(path.clone()),
+        };
+
         match self {
             AstNode::Sum(nodes) => {
-                let mut sum = 0;
-                for node in nodes {
-                    sum += node.evaluate();
+                let mut sum: u64 = 0;
+                for (i, node) in nodes.iter().enumerate() {
+                    path.push(i);
+                    let v = node.evaluate_checked_at(path)?;
+                    path.pop();
+                    sum = sum.checked_add(v).ok_or_else(|| overflow(path))?;
                 }
-                sum
+                Ok(sum)
             }
             AstNode::Product(nodes) => {
-                let mut product = 1;
-                for node in nodes {
-                    product *= node.evaluate();
+                let mut product: u64 = 1;
+                for (i, node) in nodes.iter().enumerate() {
+                    path.push(i);
+                    let v = node.evaluate_checked_at(path)?;
+                    path.pop();
+                    product = product.checked_mul(v).ok_or_else(|| overflow(path))?;
                 }
-                product
+                Ok(product)
             }
             AstNode::Minimum(nodes) => {
                 let mut minimum = u64::MAX;
-                for node in nodes {
-                    minimum = std::cmp::min(minimum, node.evaluate());
+                for (i, node) in nodes.iter().enumerate() {
+                    path.push(i);
+                    minimum = std::cmp::min(minimum, node.evaluate_checked_at(path)?);
+                    path.pop();
                 }
-                minimum
+                Ok(minimum)
             }
             AstNode::Maximum(nodes) => {
                 let mut maximum = 0;
-                for node in nodes {
-                    maximum = std::cmp::max(maximum, node.evaluate());
+                for (i, node) in nodes.iter().enumerate() {
+                    path.push(i);
+                    maximum = std::cmp::max(maximum, node.evaluate_checked_at(path)?);
+                    path.pop();
                 }
-                maximum
+                Ok(maximum)
             }
-            AstNode::Literal(v) => v,
+            AstNode::Literal(v) => Ok(*v),
+            AstNode::WideLiteral(_) => Err(EvaluateError::LiteralOverflow {
+                path: NodePath(path.clone()),
+            }),
             AstNode::GreaterThan(n1, n2) => {
-                let v1 = n1.evaluate();
-                let v2 = n2.evaluate();
-                if v1 > v2 {
-                    1
-                } else {
-                    0
-                }
+                let (v1, v2) = Self::evaluate_checked_pair(n1, n2, path)?;
+                Ok((v1 > v2) as u64)
             }
             AstNode::LessThan(n1, n2) => {
-                let v1 = n1.evaluate();
-                let v2 = n2.evaluate();
-                if v1 < v2 {
-                    1
-                } else {
-                    0
-                }
+                let (v1, v2) = Self::evaluate_checked_pair(n1, n2, path)?;
+                Ok((v1 < v2) as u64)
             }
             AstNode::EqualTo(n1, n2) => {
-                let v1 = n1.evaluate();
-                let v2 = n2.evaluate();
-                if v1 == v2 {
-                    1
-                } else {
-                    0
-                }
+                let (v1, v2) = Self::evaluate_checked_pair(n1, n2, path)?;
+                Ok((v1 == v2) as u64)
             }
         }
     }
+
+    fn evaluate_checked_pair(
+        n1: &AstNode,
+        n2: &AstNode,
+        path: &mut Vec<usize>,
+    ) -> Result<(u64, u64), EvaluateError> {
+        path.push(0);
+        let v1 = n1.evaluate_checked_at(path)?;
+        path.pop();
+        path.push(1);
+        let v2 = n2.evaluate_checked_at(path)?;
+        path.pop();
+        Ok((v1, v2))
+    }
+
+    /// Evaluates the tree with arbitrary precision, so the result is always exact.
+    pub fn evaluate_big(&self) -> BigUint {
+        match self {
+            AstNode::Sum(nodes) => nodes
+                .iter()
+                .fold(BigUint::zero(), |sum, node| sum.add(&node.evaluate_big())),
+            AstNode::Product(nodes) => nodes.iter().fold(BigUint::one(), |product, node| {
+                product.mul(&node.evaluate_big())
+            }),
+            AstNode::Minimum(nodes) => nodes
+                .iter()
+                .map(|node| node.evaluate_big())
+                .min()
+                .unwrap_or_else(|| BigUint::from(u64::MAX)),
+            AstNode::Maximum(nodes) => nodes
+                .iter()
+                .map(|node| node.evaluate_big())
+                .max()
+                .unwrap_or_else(BigUint::zero),
+            AstNode::Literal(v) => BigUint::from(*v),
+            AstNode::WideLiteral(v) => v.clone(),
+            AstNode::GreaterThan(n1, n2) => {
+                BigUint::from((n1.evaluate_big() > n2.evaluate_big()) as u64)
+            }
+            AstNode::LessThan(n1, n2) => {
+                BigUint::from((n1.evaluate_big() < n2.evaluate_big()) as u64)
+            }
+            AstNode::EqualTo(n1, n2) => {
+                BigUint::from((n1.evaluate_big() == n2.evaluate_big()) as u64)
+            }
+        }
+    }
+}
+
+/// The location of a node in the tree, as the child index taken at each level from the root.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct NodePath(pub Vec<usize>);
+
+impl std::fmt::Display for NodePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "/");
+        }
+        for index in &self.0 {
+            write!(f, "/{}", index)?;
+        }
+        Ok(())
+    }
 }
 
 pub struct AstBuilder<'a> {
@@ -115,10 +205,13 @@ impl<'a> AstBuilder<'a> {
                 let (bits, vec) = self.build_vec(packet.payload)?;
                 (bits, AstNode::Maximum(vec))
             }
-            4 => (
-                0,
-                AstNode::Literal(packet.payload.as_literal().ok_or(Error::ExpectedLiteral)?),
-            ),
+            4 => match packet.payload {
+                Payload::WideLiteral { value, .. } => (0, AstNode::WideLiteral(value)),
+                payload => (
+                    0,
+                    AstNode::Literal(payload.as_literal().ok_or(Error::ExpectedLiteral)?),
+                ),
+            },
             5 => {
                 let (b1, ast1) = self.build_bits()?;
                 let (b2, ast2) = self.build_bits()?;
@@ -149,7 +242,7 @@ impl<'a> AstBuilder<'a> {
         match payload {
             Payload::OperatorBitLength(bits) => self.build_vec_bits(bits),
             Payload::OperatorPacketLength(packets) => self.build_vec_packets(packets),
-            Payload::Literal { .. } => Err(Error::UnexpectedLiteral),
+            Payload::Literal { .. } | Payload::WideLiteral { .. } => Err(Error::UnexpectedLiteral),
         }
     }
 
@@ -191,9 +284,6 @@ pub enum Error {
     #[error("Invalid type ID: {0:?}")]
     InvalidTypeId(TypeId),
 
-    #[error("Not implemented")]
-    NotImplemented,
-
     #[error("Expected literal")]
     ExpectedLiteral,
 
@@ -204,6 +294,15 @@ pub enum Error {
     MisalignedBits,
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum EvaluateError {
+    #[error("Arithmetic overflow at node {path}")]
+    Overflow { path: NodePath },
+
+    #[error("Literal at node {path} does not fit in 64 bits")]
+    LiteralOverflow { path: NodePath },
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -304,4 +403,84 @@ mod tests {
             Ok(1)
         ));
     }
+
+    #[test]
+    fn test_evaluate_checked() {
+        let ast = AstNode::Sum(vec![
+            AstNode::Product(vec![AstNode::Literal(6), AstNode::Literal(9)]),
+            AstNode::Minimum(vec![AstNode::Literal(7), AstNode::Literal(8)]),
+        ]);
+        assert_eq!(ast.evaluate_checked().unwrap(), 61);
+
+        let ast = AstNode::Sum(vec![
+            AstNode::Literal(1),
+            AstNode::Product(vec![
+                AstNode::Literal(2),
+                AstNode::Literal(u64::MAX),
+                AstNode::Literal(3),
+            ]),
+        ]);
+        match ast.evaluate_checked() {
+            Err(EvaluateError::Overflow { path }) => {
+                assert_eq!(path, NodePath(vec![1]));
+                assert_eq!(path.to_string(), "/1");
+            }
+            other => panic!("Expected overflow, got {:?}", other),
+        }
+
+        let ast = AstNode::Sum(vec![AstNode::Literal(u64::MAX), AstNode::Literal(1)]);
+        match ast.evaluate_checked() {
+            Err(EvaluateError::Overflow { path }) => assert_eq!(path.to_string(), "/"),
+            other => panic!("Expected overflow, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_big() {
+        let ast = AstNode::Product(vec![AstNode::Literal(u64::MAX), AstNode::Literal(u64::MAX)]);
+        assert_eq!(
+            ast.evaluate_big().to_string(),
+            "340282366920938463426481119284349108225"
+        );
+
+        let ast = AstNode::GreaterThan(
+            Box::new(AstNode::Sum(vec![
+                AstNode::Literal(u64::MAX),
+                AstNode::Literal(1),
+            ])),
+            Box::new(AstNode::Literal(u64::MAX)),
+        );
+        assert_eq!(ast.evaluate_big(), BigUint::one());
+
+        for input in [
+            "C200B40A82",
+            "04005AC33890",
+            "880086C3E88112",
+            "CE00C43D881120",
+            "D8005AC2A8F0",
+            "F600BC2D8F",
+            "9C005AC2F8F0",
+            "9C0141080250320F1802104A08",
+        ] {
+            let expected = AstNode::evaluate_input(input).unwrap();
+            let actual = AstNode::evaluate_input_big(input).unwrap();
+            assert_eq!(actual, BigUint::from(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_wide_literal() {
+        // The sum of 16^17 - 1 and 1.
+        let input = "020084FFFFFFFFFFFFFFFFFFFF7881";
+        assert_eq!(
+            AstNode::evaluate_input_big(input).unwrap().to_string(),
+            "295147905179352825856"
+        );
+
+        let ast = AstBuilder::new(input).build().unwrap();
+        match ast.evaluate_checked() {
+            Err(EvaluateError::LiteralOverflow { path }) => assert_eq!(path.to_string(), "/0"),
+            other => panic!("Expected literal overflow, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,221 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// An arbitrary-precision unsigned integer, stored as little-endian base 2^32 limbs.
+///
+/// Only the handful of operations the BITS evaluator needs are implemented.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self { limbs: Vec::new() }
+    }
+
+    pub fn one() -> Self {
+        Self::from(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Builds a value from base 16 digits, most significant first.
+    pub fn from_nibbles(nibbles: &[u8]) -> Self {
+        let mut limbs = vec![0u32; nibbles.len().div_ceil(8)];
+        for (i, nibble) in nibbles.iter().rev().enumerate() {
+            limbs[i / 8] |= (*nibble as u32 & 0xf) << (i % 8 * 4);
+        }
+        let mut result = BigUint { limbs };
+        result.normalize();
+        result
+    }
+
+    /// Parses a string of decimal digits, or returns `None` if it has anything else in it.
+    pub fn from_decimal(digits: &str) -> Option<Self> {
+        if digits.is_empty() {
+            return None;
+        }
+        let ten = BigUint::from(10);
+        digits.chars().try_fold(BigUint::zero(), |value, c| {
+            let digit = c.to_digit(10)?;
+            Some(value.mul(&ten).add(&BigUint::from(digit as u64)))
+        })
+    }
+
+    /// The number of bits needed to write the value, which is zero for zero.
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    pub fn add(&self, other: &BigUint) -> BigUint {
+        let len = std::cmp::max(self.limbs.len(), other.limbs.len());
+        let mut limbs = Vec::with_capacity(len + 1);
+        let mut carry = 0;
+        for i in 0..len {
+            let a = self.limbs.get(i).copied().unwrap_or(0) as u64;
+            let b = other.limbs.get(i).copied().unwrap_or(0) as u64;
+            let sum = a + b + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        if carry > 0 {
+            limbs.push(carry as u32);
+        }
+        BigUint { limbs }
+    }
+
+    pub fn mul(&self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let product = *a as u64 * *b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = product as u32;
+                carry = product >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        let mut result = BigUint { limbs };
+        result.normalize();
+        result
+    }
+
+    /// Divides in place by a small divisor, returning the remainder.
+    fn div_rem_small(&mut self, divisor: u32) -> u32 {
+        let mut remainder: u64 = 0;
+        for limb in self.limbs.iter_mut().rev() {
+            let value = remainder << 32 | *limb as u64;
+            *limb = (value / divisor as u64) as u32;
+            remainder = value % divisor as u64;
+        }
+        self.normalize();
+        remainder as u32
+    }
+
+    fn normalize(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        let mut result = BigUint {
+            limbs: vec![value as u32, (value >> 32) as u32],
+        };
+        result.normalize();
+        result
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.pad("0");
+        }
+
+        // Peel off nine decimal digits at a time.
+        let mut value = self.clone();
+        let mut chunks = Vec::new();
+        while !value.is_zero() {
+            chunks.push(value.div_rem_small(1_000_000_000));
+        }
+
+        let mut s = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            s.push_str(&format!("{:09}", chunk));
+        }
+        f.pad(&s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(BigUint::from(1234567890123).to_string(), "1234567890123");
+        assert_eq!(BigUint::from(u64::MAX).to_string(), "18446744073709551615");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let max = BigUint::from(u64::MAX);
+        assert_eq!(max.add(&BigUint::one()).to_string(), "18446744073709551616");
+        assert_eq!(
+            max.mul(&max).to_string(),
+            "340282366920938463426481119284349108225"
+        );
+        assert_eq!(max.mul(&BigUint::zero()), BigUint::zero());
+        assert_eq!(BigUint::from(6).mul(&BigUint::from(9)), BigUint::from(54));
+    }
+
+    #[test]
+    fn test_nibbles() {
+        assert_eq!(BigUint::from_nibbles(&[]), BigUint::zero());
+        assert_eq!(
+            BigUint::from_nibbles(&[0, 0, 7, 0xe, 5]),
+            BigUint::from(0x7e5)
+        );
+        let max = BigUint::from_nibbles(&[0xf; 16]);
+        assert_eq!(max, BigUint::from(u64::MAX));
+        assert_eq!(max.bits(), 64);
+        let wide = BigUint::from_nibbles(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(wide, max.add(&BigUint::one()));
+        assert_eq!(wide.bits(), 65);
+        assert_eq!(BigUint::zero().bits(), 0);
+    }
+
+    #[test]
+    fn test_from_decimal() {
+        let max = BigUint::from(u64::MAX);
+        assert_eq!(
+            BigUint::from_decimal("18446744073709551615"),
+            Some(max.clone())
+        );
+        assert_eq!(
+            BigUint::from_decimal("018446744073709551616"),
+            Some(max.add(&BigUint::one()))
+        );
+        assert_eq!(BigUint::from_decimal("0"), Some(BigUint::zero()));
+        assert_eq!(BigUint::from_decimal(""), None);
+        assert_eq!(BigUint::from_decimal("12a"), None);
+        assert_eq!(BigUint::from_decimal("-1"), None);
+    }
+
+    #[test]
+    fn test_ordering() {
+        let max = BigUint::from(u64::MAX);
+        let big = max.add(&BigUint::one());
+        assert!(big > max);
+        assert!(BigUint::from(2) < BigUint::from(3));
+        assert_eq!(BigUint::from(3).cmp(&BigUint::from(3)), Ordering::Equal);
+    }
+}
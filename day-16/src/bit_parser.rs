@@ -1,11 +1,21 @@
 pub struct BitParser<'a> {
     input: &'a str,
     bit: u8,
+    offset: usize,
 }
 
 impl<'a> BitParser<'a> {
     pub fn new(input: &'a str) -> Self {
-        Self { input, bit: 0 }
+        Self {
+            input,
+            bit: 0,
+            offset: 0,
+        }
+    }
+
+    /// The number of bits consumed so far.
+    pub fn offset(&self) -> usize {
+        self.offset
     }
 
     fn consume_bit(&mut self) -> Result<Option<u8>, Error> {
@@ -39,6 +49,7 @@ impl<'a> BitParser<'a> {
         };
 
         self.bit += 1;
+        self.offset += 1;
         if self.bit == 4 {
             self.bit = 0;
             self.input = &self.input[1..];
@@ -75,6 +86,21 @@ pub enum Error {
     InvalidCharacter(String),
 }
 
+/// Renders a string of '0'/'1' characters as hex, padding with zeros. Handy for hand-writing
+/// transmissions in tests.
+#[cfg(test)]
+pub fn to_hex(bits: &str) -> String {
+    let mut bits = bits.to_string();
+    bits.push_str(&"0".repeat((4 - bits.len() % 4) % 4));
+    bits.as_bytes()
+        .chunks(4)
+        .map(|chunk| {
+            let nibble = u8::from_str_radix(std::str::from_utf8(chunk).unwrap(), 2).unwrap();
+            format!("{:X}", nibble)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,6 +148,7 @@ mod tests {
             "Next four bits of B"
         );
         assert_eq!(bit_parser.consume_bits(5)?, Some(0b00101), "C");
+        assert_eq!(bit_parser.offset(), 21);
         assert_eq!(bit_parser.consume_bits(4)?, None);
         Ok(())
     }
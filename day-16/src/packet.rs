@@ -1,3 +1,4 @@
+use crate::bignum::BigUint;
 use crate::bit_parser::BitParser;
 
 pub struct PacketParser<'a> {
@@ -10,6 +11,20 @@ impl<'a> PacketParser<'a> {
         Self { bit_parser }
     }
 
+    /// The number of bits consumed so far.
+    pub fn bit_offset(&self) -> usize {
+        self.bit_parser.offset()
+    }
+
+    /// Consumes the rest of the input, returning whether every remaining bit was zero.
+    pub fn consume_padding(&mut self) -> Result<bool, Error> {
+        let mut all_zero = true;
+        while let Some(bit) = self.bit_parser.consume_bits(1)? {
+            all_zero &= bit == 0;
+        }
+        Ok(all_zero)
+    }
+
     pub fn next(&mut self) -> Result<Option<Packet>, Error> {
         let version = if let Some(version) = self.bit_parser.consume_bits(3)? {
             Version(version)
@@ -45,8 +60,7 @@ impl<'a> PacketParser<'a> {
     }
 
     fn parse_literal_payload(&mut self) -> Result<Payload, Error> {
-        let mut value = 0;
-        let mut chunks = 0;
+        let mut nibbles = Vec::new();
 
         loop {
             let continuation = self
@@ -57,14 +71,27 @@ impl<'a> PacketParser<'a> {
                 .bit_parser
                 .consume_bits(4)?
                 .ok_or(Error::UnexpectedEof)?;
-            chunks += 1;
-            value = value << 4 | v as u64;
+            nibbles.push(v);
             if continuation == 0 {
                 break;
             }
         }
 
-        Ok(Payload::Literal { chunks, value })
+        // `Packet::bits` has to be able to count the whole packet.
+        if nibbles.len() > ((u16::MAX - Version::BITS - TypeId::BITS) / 5) as usize {
+            return Err(Error::PacketTooLong);
+        }
+        let chunks = nibbles.len() as u16;
+        let value = nibbles
+            .iter()
+            .try_fold(0u64, |value, v| Some(value.checked_mul(16)? | *v as u64));
+        Ok(match value {
+            Some(value) => Payload::Literal { chunks, value },
+            None => Payload::WideLiteral {
+                chunks,
+                value: BigUint::from_nibbles(&nibbles),
+            },
+        })
     }
 
     fn parse_operator_payload(&mut self) -> Result<Payload, Error> {
@@ -111,11 +138,11 @@ pub enum Error {
     #[error(transparent)]
     BitParser(#[from] crate::bit_parser::Error),
 
-    #[error("Not implemented")]
-    NotImplemented,
-
     #[error("Unexpected end of input")]
     UnexpectedEof,
+
+    #[error("Packet is longer than {} bits", u16::MAX)]
+    PacketTooLong,
 }
 
 /// Version number of the packet
@@ -144,17 +171,21 @@ impl TypeId {
     pub fn is_literal(&self) -> bool {
         self.0 == 4
     }
-
-    pub fn is_operator(&self) -> bool {
-        !self.is_literal()
-    }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Payload {
     OperatorBitLength(u16),
     OperatorPacketLength(u16),
-    Literal { chunks: u8, value: u64 },
+    Literal {
+        chunks: u16,
+        value: u64,
+    },
+    /// A literal too big for a `u64`.
+    WideLiteral {
+        chunks: u16,
+        value: BigUint,
+    },
 }
 
 impl Payload {
@@ -170,12 +201,12 @@ impl Payload {
         match self {
             Self::OperatorBitLength(..) => 1 + 15,
             Self::OperatorPacketLength(..) => 1 + 11,
-            Self::Literal { chunks, .. } => *chunks as u16 * 5,
+            Self::Literal { chunks, .. } | Self::WideLiteral { chunks, .. } => *chunks * 5,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Packet {
     pub version: Version,
     pub type_id: TypeId,
@@ -313,4 +344,40 @@ mod tests {
         assert_eq!(packet, None);
         Ok(())
     }
+
+    #[test]
+    fn test_literal_overflow() -> Result<()> {
+        let mut packet_parser = PacketParser::new("13FFFFFFFFFFFFFFFFFFBC");
+        let packet = packet_parser.next()?.expect("Expected packet");
+        assert_eq!(packet.payload.as_literal(), Some(u64::MAX));
+
+        let mut packet_parser = PacketParser::new("13FFFFFFFFFFFFFFFFFFFDE");
+        let packet = packet_parser.next()?.expect("Expected packet");
+        assert_eq!(packet.payload.as_literal(), None);
+        assert_eq!(
+            packet.payload,
+            Payload::WideLiteral {
+                chunks: 17,
+                value: BigUint::from(u64::MAX)
+                    .mul(&BigUint::from(16))
+                    .add(&BigUint::from(15)),
+            }
+        );
+        assert_eq!(packet.bits(), 91);
+
+        // 300 groups of 0001: well past 255 groups, but still valid.
+        let bits = format!("000100{}00001", "10001".repeat(299));
+        let input = crate::bit_parser::to_hex(&bits);
+        let mut packet_parser = PacketParser::new(&input);
+        let packet = packet_parser.next()?.expect("Expected packet");
+        assert_eq!(
+            packet.payload,
+            Payload::WideLiteral {
+                chunks: 300,
+                value: BigUint::from_nibbles(&[1; 300]),
+            }
+        );
+        assert_eq!(packet.bits(), 6 + 300 * 5);
+        Ok(())
+    }
 }
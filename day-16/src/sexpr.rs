@@ -0,0 +1,292 @@
+//! A human-writable S-expression format for BITS expressions, such as
+//! `(sum (product 6 9) (min 7 8 9))`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ast::AstNode;
+use crate::bignum::BigUint;
+
+impl fmt::Display for AstNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, children): (&str, Vec<&AstNode>) = match self {
+            AstNode::Literal(v) => return write!(f, "{}", v),
+            AstNode::WideLiteral(v) => return write!(f, "{}", v),
+            AstNode::Sum(nodes) => ("sum", nodes.iter().collect()),
+            AstNode::Product(nodes) => ("product", nodes.iter().collect()),
+            AstNode::Minimum(nodes) => ("min", nodes.iter().collect()),
+            AstNode::Maximum(nodes) => ("max", nodes.iter().collect()),
+            AstNode::GreaterThan(n1, n2) => ("gt", vec![n1, n2]),
+            AstNode::LessThan(n1, n2) => ("lt", vec![n1, n2]),
+            AstNode::EqualTo(n1, n2) => ("eq", vec![n1, n2]),
+        };
+
+        write!(f, "({}", name)?;
+        for child in children {
+            write!(f, " {}", child)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl FromStr for AstNode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = Tokenizer::new(s);
+        let node = parse_node(&mut tokens)?;
+        match tokens.next() {
+            None => Ok(node),
+            Some(token) => Err(Error::TrailingInput(token.to_string())),
+        }
+    }
+}
+
+fn parse_node(tokens: &mut Tokenizer) -> Result<AstNode, Error> {
+    match tokens.next().ok_or(Error::UnexpectedEof)? {
+        Token::Open => {}
+        Token::Close => return Err(Error::UnexpectedToken(")".to_string())),
+        Token::Atom(atom) => {
+            if let Ok(value) = atom.parse() {
+                return Ok(AstNode::Literal(value));
+            }
+            // Too big for a `u64`, or not a number at all.
+            return BigUint::from_decimal(atom)
+                .map(AstNode::WideLiteral)
+                .ok_or_else(|| Error::InvalidLiteral(atom.to_string()));
+        }
+    }
+
+    let operator = match tokens.next().ok_or(Error::UnexpectedEof)? {
+        Token::Atom(atom) => atom,
+        token => return Err(Error::UnexpectedToken(token.to_string())),
+    };
+
+    let mut children = Vec::new();
+    loop {
+        match tokens.peek().ok_or(Error::UnexpectedEof)? {
+            Token::Close => {
+                tokens.next();
+                break;
+            }
+            _ => children.push(parse_node(tokens)?),
+        }
+    }
+
+    match operator {
+        "sum" => Ok(AstNode::Sum(children)),
+        "product" => Ok(AstNode::Product(children)),
+        "min" => Ok(AstNode::Minimum(children)),
+        "max" => Ok(AstNode::Maximum(children)),
+        "gt" | "lt" | "eq" => {
+            let found = children.len();
+            let (n1, n2) = match <[AstNode; 2]>::try_from(children) {
+                Ok([n1, n2]) => (Box::new(n1), Box::new(n2)),
+                Err(_) => {
+                    return Err(Error::InvalidArity {
+                        operator: operator.to_string(),
+                        found,
+                    })
+                }
+            };
+            Ok(match operator {
+                "gt" => AstNode::GreaterThan(n1, n2),
+                "lt" => AstNode::LessThan(n1, n2),
+                _ => AstNode::EqualTo(n1, n2),
+            })
+        }
+        _ => Err(Error::UnknownOperator(operator.to_string())),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Token<'a> {
+    Open,
+    Close,
+    Atom(&'a str),
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::Atom(atom) => write!(f, "{}", atom),
+        }
+    }
+}
+
+struct Tokenizer<'a> {
+    input: &'a str,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input }
+    }
+
+    fn peek(&self) -> Option<Token<'a>> {
+        self.split().map(|(token, _)| token)
+    }
+
+    fn split(&self) -> Option<(Token<'a>, &'a str)> {
+        let input = self.input.trim_start();
+        match input.chars().next()? {
+            '(' => Some((Token::Open, &input[1..])),
+            ')' => Some((Token::Close, &input[1..])),
+            _ => {
+                let end = input
+                    .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                    .unwrap_or(input.len());
+                Some((Token::Atom(&input[..end]), &input[end..]))
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (token, rest) = self.split()?;
+        self.input = rest;
+        Some(token)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Unexpected end of input")]
+    UnexpectedEof,
+
+    #[error("Unexpected token: {0}")]
+    UnexpectedToken(String),
+
+    #[error("Unexpected input after expression: {0}")]
+    TrailingInput(String),
+
+    #[error("Invalid literal: {0}")]
+    InvalidLiteral(String),
+
+    #[error("Unknown operator: {0}")]
+    UnknownOperator(String),
+
+    #[error("Operator '{operator}' takes exactly 2 operands, found {found}")]
+    InvalidArity { operator: String, found: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let ast = AstNode::Sum(vec![
+            AstNode::Product(vec![AstNode::Literal(6), AstNode::Literal(9)]),
+            AstNode::Minimum(vec![
+                AstNode::Literal(7),
+                AstNode::Literal(8),
+                AstNode::Literal(9),
+            ]),
+        ]);
+        assert_eq!(ast.to_string(), "(sum (product 6 9) (min 7 8 9))");
+
+        let ast = AstNode::EqualTo(
+            Box::new(AstNode::Maximum(vec![])),
+            Box::new(AstNode::LessThan(
+                Box::new(AstNode::Literal(1)),
+                Box::new(AstNode::Literal(2)),
+            )),
+        );
+        assert_eq!(ast.to_string(), "(eq (max) (lt 1 2))");
+    }
+
+    #[test]
+    fn test_parse() {
+        let ast: AstNode = "(sum (product 6 9) (min 7 8 9))".parse().unwrap();
+        assert_eq!(
+            ast,
+            AstNode::Sum(vec![
+                AstNode::Product(vec![AstNode::Literal(6), AstNode::Literal(9)]),
+                AstNode::Minimum(vec![
+                    AstNode::Literal(7),
+                    AstNode::Literal(8),
+                    AstNode::Literal(9),
+                ]),
+            ])
+        );
+
+        let ast: AstNode = "  ( gt\n(max 1 2)(sum) )  ".parse().unwrap();
+        assert_eq!(ast.to_string(), "(gt (max 1 2) (sum))");
+        assert_eq!("17".parse::<AstNode>().unwrap(), AstNode::Literal(17));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            "(lt 1 2 3)".parse::<AstNode>(),
+            Err(Error::InvalidArity { found: 3, .. })
+        ));
+        assert!(matches!(
+            "(eq 1)".parse::<AstNode>(),
+            Err(Error::InvalidArity { found: 1, .. })
+        ));
+        assert_eq!(
+            "(gt)".parse::<AstNode>().unwrap_err().to_string(),
+            "Operator 'gt' takes exactly 2 operands, found 0"
+        );
+        assert!(matches!(
+            "(sum 1 2".parse::<AstNode>(),
+            Err(Error::UnexpectedEof)
+        ));
+        assert!(matches!(
+            "(sum 1) 2".parse::<AstNode>(),
+            Err(Error::TrailingInput(_))
+        ));
+        assert!(matches!(
+            "(div 1 2)".parse::<AstNode>(),
+            Err(Error::UnknownOperator(_))
+        ));
+        assert!(matches!(
+            "(sum 1 -2)".parse::<AstNode>(),
+            Err(Error::InvalidLiteral(_))
+        ));
+        assert!(matches!(
+            "((sum) 1)".parse::<AstNode>(),
+            Err(Error::UnexpectedToken(_))
+        ));
+        assert!(matches!(
+            ")".parse::<AstNode>(),
+            Err(Error::UnexpectedToken(_))
+        ));
+    }
+
+    #[test]
+    fn test_text_matches_hex() {
+        for (text, hex) in [
+            ("(sum 1 2)", "C200B40A82"),
+            ("(product 6 9)", "04005AC33890"),
+            ("(min 7 8 9)", "880086C3E88112"),
+            ("(max 7 8 9)", "CE00C43D881120"),
+            ("(lt 5 15)", "D8005AC2A8F0"),
+            ("(gt 5 15)", "F600BC2D8F"),
+            ("(eq 5 15)", "9C005AC2F8F0"),
+            ("(eq (sum 1 3) (product 2 2))", "9C0141080250320F1802104A08"),
+            (
+                "(sum 295147905179352825855 1)",
+                "020084FFFFFFFFFFFFFFFFFFFF7881",
+            ),
+        ] {
+            let from_text: AstNode = text.parse().unwrap();
+            let from_hex = crate::ast::AstBuilder::new(hex).build().unwrap();
+            assert_eq!(from_text, from_hex, "{}", hex);
+            assert_eq!(from_hex.to_string(), text);
+            assert_eq!(
+                from_text.evaluate_big(),
+                AstNode::evaluate_input_big(hex).unwrap(),
+                "{}",
+                text
+            );
+        }
+    }
+}
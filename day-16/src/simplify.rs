@@ -0,0 +1,239 @@
+//! Rewrites an `AstNode` into a smaller equivalent tree, for shrinking transmissions before
+//! re-encoding them.
+
+use crate::ast::AstNode;
+
+/// The result of `AstNode::simplify`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Simplified {
+    pub node: AstNode,
+    /// How many fewer bits the simplified tree takes to encode than the original.
+    pub saved_bits: u64,
+}
+
+impl AstNode {
+    /// Simplifies the tree:
+    ///
+    /// * nested `Sum`, `Product`, `Minimum` and `Maximum` operators are flattened into their
+    ///   parent of the same kind,
+    /// * operators with a single operand are replaced by that operand,
+    /// * literal operands are folded together, and subtrees made only of literals are replaced by
+    ///   their value.
+    ///
+    /// Folding that would overflow a `u64` is skipped, so the result always evaluates the same as
+    /// the original. Wide literals are left as they are, and so is any operator whose rewrite
+    /// would take more bits to encode, so the result is never larger than the original.
+    pub fn simplify(self) -> Simplified {
+        let before = self.encoded_bits();
+        let node = simplify_node(self);
+        let saved_bits = before.saturating_sub(node.encoded_bits());
+        Simplified { node, saved_bits }
+    }
+
+    /// The number of bits needed to encode this tree as a transmission (excluding padding),
+    /// using the shortest length type for each operator.
+    pub fn encoded_bits(&self) -> u64 {
+        const HEADER: u64 = 6;
+
+        let children: Vec<&AstNode> = match self {
+            AstNode::Literal(v) => {
+                let chunks = std::cmp::max(1, (64 - v.leading_zeros() as u64).div_ceil(4));
+                return HEADER + 5 * chunks;
+            }
+            AstNode::WideLiteral(v) => return HEADER + 5 * v.bits().div_ceil(4),
+            AstNode::Sum(nodes)
+            | AstNode::Product(nodes)
+            | AstNode::Minimum(nodes)
+            | AstNode::Maximum(nodes) => nodes.iter().collect(),
+            AstNode::GreaterThan(n1, n2) | AstNode::LessThan(n1, n2) | AstNode::EqualTo(n1, n2) => {
+                vec![n1, n2]
+            }
+        };
+
+        // An 11-bit packet count is shorter than a 15-bit length, as long as it fits.
+        let length_type = if children.len() < 1 << 11 {
+            1 + 11
+        } else {
+            1 + 15
+        };
+        HEADER + length_type + children.iter().map(|c| c.encoded_bits()).sum::<u64>()
+    }
+}
+
+/// Simplifies the children, then the node itself, unless rewriting the node would take more bits
+/// to encode than leaving it as it is (as folding an empty `min` into `u64::MAX` would).
+fn simplify_node(node: AstNode) -> AstNode {
+    let simplify_boxed = |node: Box<AstNode>| Box::new(simplify_node(*node));
+    let node = match node {
+        AstNode::Literal(_) | AstNode::WideLiteral(_) => return node,
+        AstNode::Sum(nodes) => AstNode::Sum(nodes.into_iter().map(simplify_node).collect()),
+        AstNode::Product(nodes) => AstNode::Product(nodes.into_iter().map(simplify_node).collect()),
+        AstNode::Minimum(nodes) => AstNode::Minimum(nodes.into_iter().map(simplify_node).collect()),
+        AstNode::Maximum(nodes) => AstNode::Maximum(nodes.into_iter().map(simplify_node).collect()),
+        AstNode::GreaterThan(n1, n2) => {
+            AstNode::GreaterThan(simplify_boxed(n1), simplify_boxed(n2))
+        }
+        AstNode::LessThan(n1, n2) => AstNode::LessThan(simplify_boxed(n1), simplify_boxed(n2)),
+        AstNode::EqualTo(n1, n2) => AstNode::EqualTo(simplify_boxed(n1), simplify_boxed(n2)),
+    };
+
+    let rewritten = match node.clone() {
+        AstNode::Sum(nodes) => simplify_variadic(nodes, AstNode::Sum, 0, u64::checked_add),
+        AstNode::Product(nodes) => simplify_variadic(nodes, AstNode::Product, 1, u64::checked_mul),
+        AstNode::Minimum(nodes) => {
+            simplify_variadic(nodes, AstNode::Minimum, u64::MAX, |a, b| Some(a.min(b)))
+        }
+        AstNode::Maximum(nodes) => {
+            simplify_variadic(nodes, AstNode::Maximum, 0, |a, b| Some(a.max(b)))
+        }
+        AstNode::GreaterThan(n1, n2) => {
+            simplify_comparison(*n1, *n2, AstNode::GreaterThan, |a, b| a > b)
+        }
+        AstNode::LessThan(n1, n2) => simplify_comparison(*n1, *n2, AstNode::LessThan, |a, b| a < b),
+        AstNode::EqualTo(n1, n2) => simplify_comparison(*n1, *n2, AstNode::EqualTo, |a, b| a == b),
+        leaf => leaf,
+    };
+    if rewritten.encoded_bits() <= node.encoded_bits() {
+        rewritten
+    } else {
+        node
+    }
+}
+
+/// Simplifies an associative, commutative operator whose operands are already simplified.
+/// `identity` is what the operator evaluates to with no operands, and `combine` folds two literals
+/// (returning `None` on overflow).
+fn simplify_variadic(
+    nodes: Vec<AstNode>,
+    constructor: fn(Vec<AstNode>) -> AstNode,
+    identity: u64,
+    combine: fn(u64, u64) -> Option<u64>,
+) -> AstNode {
+    let mut operands = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        // Flatten children of the same kind into this node.
+        match (constructor(Vec::new()), node) {
+            (AstNode::Sum(_), AstNode::Sum(inner))
+            | (AstNode::Product(_), AstNode::Product(inner))
+            | (AstNode::Minimum(_), AstNode::Minimum(inner))
+            | (AstNode::Maximum(_), AstNode::Maximum(inner)) => operands.extend(inner),
+            (_, node) => operands.push(node),
+        }
+    }
+
+    // Fold all the literal operands into one, unless that would overflow.
+    let mut folded: Option<u64> = None;
+    let mut others = Vec::with_capacity(operands.len());
+    let mut literals = Vec::new();
+    for operand in operands {
+        match operand {
+            AstNode::Literal(v) => literals.push(v),
+            node => others.push(node),
+        }
+    }
+    let mut overflowed = false;
+    for v in &literals {
+        folded = match folded {
+            None => Some(*v),
+            Some(acc) => match combine(acc, *v) {
+                Some(acc) => Some(acc),
+                None => {
+                    overflowed = true;
+                    break;
+                }
+            },
+        };
+    }
+
+    let mut operands = others;
+    if overflowed {
+        operands.extend(literals.into_iter().map(AstNode::Literal));
+    } else if let Some(v) = folded {
+        // A literal that is the operator's identity doesn't change the result.
+        if v != identity || operands.is_empty() {
+            operands.push(AstNode::Literal(v));
+        }
+    }
+
+    match operands.len() {
+        0 => AstNode::Literal(identity),
+        1 => operands.pop().expect("One operand"),
+        _ => constructor(operands),
+    }
+}
+
+fn simplify_comparison(
+    n1: AstNode,
+    n2: AstNode,
+    constructor: fn(Box<AstNode>, Box<AstNode>) -> AstNode,
+    compare: fn(u64, u64) -> bool,
+) -> AstNode {
+    match (n1, n2) {
+        (AstNode::Literal(a), AstNode::Literal(b)) => AstNode::Literal(compare(a, b) as u64),
+        (n1, n2) => constructor(Box::new(n1), Box::new(n2)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simplify(text: &str) -> (String, u64) {
+        let ast: AstNode = text.parse().unwrap();
+        let expected = ast.evaluate_big();
+        let simplified = ast.simplify();
+        assert_eq!(simplified.node.evaluate_big(), expected, "{}", text);
+        (simplified.node.to_string(), simplified.saved_bits)
+    }
+
+    #[test]
+    fn test_encoded_bits() {
+        let ast = crate::ast::AstBuilder::new("D2FE28").build().unwrap();
+        assert_eq!(ast.encoded_bits(), 21);
+        let ast = crate::ast::AstBuilder::new("EE00D40C823060")
+            .build()
+            .unwrap();
+        assert_eq!(ast.encoded_bits(), 51);
+    }
+
+    #[test]
+    fn test_simplify() {
+        assert_eq!(simplify("(sum (product 6 9) (min 7 8 9))").0, "61");
+        assert_eq!(simplify("(sum 1 2)"), ("3".to_string(), 29));
+        assert_eq!(
+            simplify("(gt (sum (sum (eq 1 2) (max)) 7) (lt 3 4))").0,
+            "1"
+        );
+        assert_eq!(simplify("(min (max 5))").0, "5");
+        // Folding an empty `min` into `u64::MAX` would take more bits, so it's kept.
+        assert_eq!(simplify("(min)"), ("(min)".to_string(), 0));
+        assert_eq!(
+            simplify("(sum (min) (min))"),
+            ("(sum (min) (min))".to_string(), 0)
+        );
+        assert_eq!(simplify("(sum (sum 1 2) (min))").0, "(sum (min) 3)");
+    }
+
+    #[test]
+    fn test_simplify_overflow() {
+        // Every leaf is a literal, so only folds that would overflow leave structure behind.
+        let ast = AstNode::Sum(vec![
+            AstNode::Literal(1),
+            AstNode::Sum(vec![
+                AstNode::Literal(u64::MAX),
+                AstNode::Product(vec![AstNode::Literal(2)]),
+            ]),
+        ]);
+        let simplified = ast.clone().simplify();
+        assert_eq!(
+            simplified.node,
+            AstNode::Sum(vec![
+                AstNode::Literal(1),
+                AstNode::Literal(u64::MAX),
+                AstNode::Literal(2),
+            ])
+        );
+        assert_eq!(simplified.node.evaluate_big(), ast.evaluate_big());
+        assert_eq!(simplified.saved_bits, 36);
+    }
+}
@@ -0,0 +1,296 @@
+//! Evaluates a transmission in a single pass over its packets, without building an `AstNode`.
+//!
+//! Operators that are still waiting on sub-packets are kept on an explicit stack, so memory use
+//! is bounded by the nesting depth of the transmission and deep nesting can't overflow the call
+//! stack.
+
+use crate::ast::{EvaluateError, NodePath};
+use crate::packet::{PacketParser, Payload, TypeId};
+
+pub struct StreamEvaluator<'a> {
+    packet_parser: PacketParser<'a>,
+}
+
+impl<'a> StreamEvaluator<'a> {
+    pub fn new(input: &'a str) -> Self {
+        let packet_parser = PacketParser::new(input);
+        Self { packet_parser }
+    }
+
+    pub fn evaluate(&mut self) -> Result<u64, Error> {
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut consumed_bits: u64 = 0;
+
+        loop {
+            let packet = self.packet_parser.next()?.ok_or(Error::ExpectedPacket)?;
+            consumed_bits += packet.bits() as u64;
+
+            let mut completed = match packet.payload {
+                Payload::Literal { value, .. } => Some(value),
+                Payload::WideLiteral { .. } => {
+                    return Err(Error::Evaluate(EvaluateError::LiteralOverflow {
+                        path: NodePath(stack.iter().map(|frame| frame.children).collect()),
+                    }))
+                }
+                Payload::OperatorBitLength(bits) => {
+                    let operator = Operator::from_type_id(packet.type_id)?;
+                    stack.push(Frame::new(operator, Limit::Bits(bits), consumed_bits));
+                    None
+                }
+                Payload::OperatorPacketLength(packets) => {
+                    let operator = Operator::from_type_id(packet.type_id)?;
+                    stack.push(Frame::new(operator, Limit::Packets(packets), consumed_bits));
+                    None
+                }
+            };
+
+            // Feed the finished value up the stack, closing every operator it completes.
+            while let Some(mut frame) = stack.pop() {
+                if let Some(value) = completed.take() {
+                    frame.accept(value, &stack)?;
+                }
+                if !frame.is_complete(consumed_bits)? {
+                    stack.push(frame);
+                    break;
+                }
+                completed = Some(frame.finish()?);
+            }
+
+            if stack.is_empty() {
+                return completed.ok_or(Error::ExpectedPacket);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Operator {
+    Sum,
+    Product,
+    Minimum,
+    Maximum,
+    GreaterThan,
+    LessThan,
+    EqualTo,
+}
+
+impl Operator {
+    fn from_type_id(type_id: TypeId) -> Result<Self, Error> {
+        match type_id.into_u8() {
+            0 => Ok(Operator::Sum),
+            1 => Ok(Operator::Product),
+            2 => Ok(Operator::Minimum),
+            3 => Ok(Operator::Maximum),
+            5 => Ok(Operator::GreaterThan),
+            6 => Ok(Operator::LessThan),
+            7 => Ok(Operator::EqualTo),
+            _ => Err(Error::InvalidTypeId(type_id)),
+        }
+    }
+
+    fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Operator::GreaterThan | Operator::LessThan | Operator::EqualTo
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Limit {
+    Bits(u16),
+    Packets(u16),
+}
+
+/// An operator that has been opened but not yet closed.
+#[derive(Debug)]
+struct Frame {
+    operator: Operator,
+    limit: Limit,
+    start_bits: u64,
+    children: usize,
+    value: u64,
+    first: Option<u64>,
+}
+
+impl Frame {
+    fn new(operator: Operator, limit: Limit, start_bits: u64) -> Self {
+        let value = match operator {
+            Operator::Product => 1,
+            Operator::Minimum => u64::MAX,
+            _ => 0,
+        };
+        Self {
+            operator,
+            limit,
+            start_bits,
+            children: 0,
+            value,
+            first: None,
+        }
+    }
+
+    /// Folds in the value of the next sub-packet. `ancestors` are the frames enclosing this one,
+    /// and are only used to report where an overflow happened.
+    fn accept(&mut self, v: u64, ancestors: &[Frame]) -> Result<(), Error> {
+        let overflow = || {
+            Error::Evaluate(EvaluateError::Overflow {
+                path: NodePath(ancestors.iter().map(|frame| frame.children).collect()),
+            })
+        };
+
+        match self.operator {
+            Operator::Sum => self.value = self.value.checked_add(v).ok_or_else(overflow)?,
+            Operator::Product => self.value = self.value.checked_mul(v).ok_or_else(overflow)?,
+            Operator::Minimum => self.value = std::cmp::min(self.value, v),
+            Operator::Maximum => self.value = std::cmp::max(self.value, v),
+            Operator::GreaterThan | Operator::LessThan | Operator::EqualTo => {
+                match (self.children, self.first) {
+                    (0, _) => self.first = Some(v),
+                    (1, Some(first)) => {
+                        self.value = match self.operator {
+                            Operator::GreaterThan => first > v,
+                            Operator::LessThan => first < v,
+                            _ => first == v,
+                        } as u64
+                    }
+                    _ => return Err(Error::InvalidArity(self.children + 1)),
+                }
+            }
+        }
+        self.children += 1;
+        Ok(())
+    }
+
+    fn is_complete(&self, consumed_bits: u64) -> Result<bool, Error> {
+        match self.limit {
+            Limit::Bits(bits) => {
+                let used = consumed_bits - self.start_bits;
+                if used > bits as u64 {
+                    return Err(Error::MisalignedBits);
+                }
+                Ok(used == bits as u64)
+            }
+            Limit::Packets(packets) => Ok(self.children == packets as usize),
+        }
+    }
+
+    fn finish(self) -> Result<u64, Error> {
+        if self.operator.is_comparison() && self.children != 2 {
+            return Err(Error::InvalidArity(self.children));
+        }
+        Ok(self.value)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    PacketParser(#[from] crate::packet::Error),
+
+    #[error(transparent)]
+    Evaluate(#[from] EvaluateError),
+
+    #[error("Expected packet but did not receive one")]
+    ExpectedPacket,
+
+    #[error("Invalid type ID: {0:?}")]
+    InvalidTypeId(TypeId),
+
+    #[error("Comparison operator has {0} sub-packets, expected 2")]
+    InvalidArity(usize),
+
+    #[error("Misaligned bits")]
+    MisalignedBits,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::AstNode;
+    use crate::bit_parser::to_hex;
+
+    #[test]
+    fn test_matches_ast() {
+        for input in [
+            "C200B40A82",
+            "04005AC33890",
+            "880086C3E88112",
+            "CE00C43D881120",
+            "D8005AC2A8F0",
+            "F600BC2D8F",
+            "9C005AC2F8F0",
+            "9C0141080250320F1802104A08",
+            "D2FE28",
+            "38006F45291200",
+            "EE00D40C823060",
+            "8A004A801A8002F478",
+            "620080001611562C8802118E34",
+            "C0015000016115A2E0802F182340",
+            "A0016C880162017C3686B18A3D4780",
+        ] {
+            let expected = AstNode::evaluate_input(input).unwrap();
+            let actual = StreamEvaluator::new(input).evaluate().unwrap();
+            assert_eq!(actual, expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        // 100,000 nested single-packet sums around the literal 5.
+        let depth = 100_000;
+        let mut bits = String::new();
+        for _ in 0..depth {
+            bits.push_str("000000");
+            bits.push('1');
+            bits.push_str("00000000001");
+        }
+        bits.push_str("000100");
+        bits.push_str("00101");
+
+        let input = to_hex(&bits);
+        assert_eq!(StreamEvaluator::new(&input).evaluate().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_errors() {
+        // (lt 1 2 3), written as a packet-count operator with three sub-packets.
+        let bits = [
+            "000110100000000011",
+            "00010000001",
+            "00010000010",
+            "00010000011",
+        ]
+        .concat();
+        assert!(matches!(
+            StreamEvaluator::new(&to_hex(&bits)).evaluate(),
+            Err(Error::InvalidArity(3))
+        ));
+
+        // (sum (product 2 max 3)): overflow is reported at the product node.
+        let max = "1111111111111111111111111111111111111111111111111111111111111111";
+        let mut bits = String::from("000000100000000001");
+        bits.push_str("000001100000000011");
+        bits.push_str("00010000010");
+        bits.push_str("000100");
+        for (i, chunk) in max.as_bytes().chunks(4).enumerate() {
+            bits.push(if i == 15 { '0' } else { '1' });
+            bits.push_str(std::str::from_utf8(chunk).unwrap());
+        }
+        bits.push_str("00010000011");
+        match StreamEvaluator::new(&to_hex(&bits)).evaluate() {
+            Err(Error::Evaluate(EvaluateError::Overflow { path })) => {
+                assert_eq!(path, NodePath(vec![0]))
+            }
+            other => panic!("Expected overflow, got {:?}", other),
+        }
+
+        // (sum 16^17-1 1): the first literal doesn't fit in 64 bits.
+        match StreamEvaluator::new("020084FFFFFFFFFFFFFFFFFFFF7881").evaluate() {
+            Err(Error::Evaluate(EvaluateError::LiteralOverflow { path })) => {
+                assert_eq!(path, NodePath(vec![0]))
+            }
+            other => panic!("Expected literal overflow, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! Checks a transmission against the structural rules of BITS, collecting every violation
+//! rather than stopping at the first one.
+
+use crate::packet::{PacketParser, Payload, TypeId};
+
+/// Validates a transmission, returning every violation found.
+///
+/// Only problems that make it impossible to keep reading packets (bad characters or a truncated
+/// transmission) are returned as an `Error`.
+pub fn validate(input: &str) -> Result<Vec<Violation>, Error> {
+    let mut packet_parser = PacketParser::new(input);
+    let mut violations = Vec::new();
+    let mut stack: Vec<Frame> = Vec::new();
+
+    loop {
+        let offset = packet_parser.bit_offset();
+        let packet = packet_parser.next()?.ok_or(Error::ExpectedPacket)?;
+
+        let mut completed = match packet.payload {
+            Payload::Literal { chunks, value } => {
+                let needed = std::cmp::max(1, (64 - value.leading_zeros() as u16).div_ceil(4));
+                if chunks > needed {
+                    violations.push(Violation {
+                        offset,
+                        kind: ViolationKind::OverlongLiteral { chunks, needed },
+                    });
+                }
+                true
+            }
+            Payload::WideLiteral { chunks, value } => {
+                let needed = value.bits().div_ceil(4) as u16;
+                if chunks > needed {
+                    violations.push(Violation {
+                        offset,
+                        kind: ViolationKind::OverlongLiteral { chunks, needed },
+                    });
+                }
+                true
+            }
+            Payload::OperatorBitLength(bits) => {
+                stack.push(Frame {
+                    type_id: packet.type_id,
+                    offset,
+                    limit: Limit::Bits(bits),
+                    start: packet_parser.bit_offset(),
+                    children: 0,
+                });
+                false
+            }
+            Payload::OperatorPacketLength(packets) => {
+                stack.push(Frame {
+                    type_id: packet.type_id,
+                    offset,
+                    limit: Limit::Packets(packets),
+                    start: packet_parser.bit_offset(),
+                    children: 0,
+                });
+                false
+            }
+        };
+
+        // Close every operator that the packet just read completes.
+        while let Some(mut frame) = stack.pop() {
+            if completed {
+                frame.children += 1;
+            }
+            let is_complete = match frame.limit {
+                Limit::Bits(bits) => {
+                    let actual = packet_parser.bit_offset() - frame.start;
+                    if actual > bits as usize {
+                        violations.push(Violation {
+                            offset: frame.offset,
+                            kind: ViolationKind::MisalignedBits {
+                                declared: bits,
+                                actual,
+                            },
+                        });
+                    }
+                    actual >= bits as usize
+                }
+                Limit::Packets(packets) => frame.children == packets as usize,
+            };
+            if !is_complete {
+                stack.push(frame);
+                break;
+            }
+
+            if matches!(frame.type_id.into_u8(), 5..=7) && frame.children != 2 {
+                violations.push(Violation {
+                    offset: frame.offset,
+                    kind: ViolationKind::ComparisonArity {
+                        type_id: frame.type_id,
+                        found: frame.children,
+                    },
+                });
+            }
+            completed = true;
+        }
+
+        if stack.is_empty() {
+            break;
+        }
+    }
+
+    let offset = packet_parser.bit_offset();
+    if !packet_parser.consume_padding()? {
+        violations.push(Violation {
+            offset,
+            kind: ViolationKind::NonZeroPadding,
+        });
+    }
+
+    Ok(violations)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Limit {
+    Bits(u16),
+    Packets(u16),
+}
+
+/// An operator whose sub-packets are still being read.
+struct Frame {
+    type_id: TypeId,
+    offset: usize,
+    limit: Limit,
+    start: usize,
+    children: usize,
+}
+
+/// A rule broken by a transmission, and the bit offset of the packet (or padding) that broke it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Violation {
+    pub offset: usize,
+    pub kind: ViolationKind,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bit {}: {}", self.offset, self.kind)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+pub enum ViolationKind {
+    #[error("Comparison packet (type {type_id:?}) has {found} sub-packets, expected 2")]
+    ComparisonArity { type_id: TypeId, found: usize },
+
+    #[error("Trailing padding contains non-zero bits")]
+    NonZeroPadding,
+
+    #[error("Sub-packets take {actual} bits, but the operator declared {declared}")]
+    MisalignedBits { declared: u16, actual: usize },
+
+    #[error("Literal uses {chunks} groups, but only {needed} are needed")]
+    OverlongLiteral { chunks: u16, needed: u16 },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    PacketParser(#[from] crate::packet::Error),
+
+    #[error("Expected packet but did not receive one")]
+    ExpectedPacket,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit_parser::to_hex;
+
+    #[test]
+    fn test_valid_examples() {
+        for input in [
+            "C200B40A82",
+            "04005AC33890",
+            "880086C3E88112",
+            "CE00C43D881120",
+            "D8005AC2A8F0",
+            "F600BC2D8F",
+            "9C005AC2F8F0",
+            "9C0141080250320F1802104A08",
+            "D2FE28",
+            "38006F45291200",
+            "EE00D40C823060",
+        ] {
+            assert_eq!(validate(input).unwrap(), vec![], "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_reports_every_violation() {
+        let bits = [
+            // Sum, declaring 55 bits of sub-packets (they actually take 56)
+            "0000000000000000110111",
+            // LessThan with three sub-packets
+            "000110100000000011",
+            "00010000001",
+            "00010000010",
+            // Literal 3, written with a redundant leading group
+            "0001001000000011",
+            // Non-zero padding
+            "1",
+        ]
+        .concat();
+
+        let violations = validate(&to_hex(&bits)).unwrap();
+        assert_eq!(violations.len(), 4, "{:?}", violations);
+        assert_eq!(
+            violations[0],
+            Violation {
+                offset: 62,
+                kind: ViolationKind::OverlongLiteral {
+                    chunks: 2,
+                    needed: 1
+                },
+            }
+        );
+        assert!(matches!(
+            violations[1],
+            Violation {
+                offset: 22,
+                kind: ViolationKind::ComparisonArity { found: 3, .. },
+            }
+        ));
+        assert_eq!(
+            violations[2],
+            Violation {
+                offset: 0,
+                kind: ViolationKind::MisalignedBits {
+                    declared: 55,
+                    actual: 56
+                },
+            }
+        );
+        assert_eq!(
+            violations[3],
+            Violation {
+                offset: 78,
+                kind: ViolationKind::NonZeroPadding,
+            }
+        );
+        assert_eq!(
+            violations[2].to_string(),
+            "bit 0: Sub-packets take 56 bits, but the operator declared 55"
+        );
+    }
+
+    #[test]
+    fn test_truncated() {
+        assert!(matches!(validate("C200B40A"), Err(Error::ExpectedPacket)));
+    }
+}